@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::mpsc;
+use tauri::async_runtime;
+use tauri::Manager;
+
+use super::job;
+use super::presets::{self, Preset};
+use super::project::{self, CreateOptions};
+
+// 批量创建：读取 JSON / TOML 清单，逐个或经任务队列并发创建，结束后生成汇总报告
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchMode {
+  // 上一个项目结束后再提交下一个
+  #[default]
+  Sequential,
+  // 全部提交到任务队列，并发数由设置决定
+  Queue,
+}
+
+// 清单中的公共默认值，项目条目中的同名字段优先
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BatchDefaults {
+  pub preset: Option<String>,
+  pub directory: Option<String>,
+  pub version: Option<String>,
+  pub lang: Option<String>,
+  pub features: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BatchItem {
+  pub name: String,
+  // 引用已保存的预设；条目中显式给出的字段优先于预设
+  #[serde(default)]
+  pub preset: Option<String>,
+  #[serde(default)]
+  pub directory: Option<String>,
+  #[serde(default)]
+  pub version: Option<String>,
+  #[serde(default)]
+  pub lang: Option<String>,
+  #[serde(default)]
+  pub features: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BatchManifest {
+  #[serde(default)]
+  pub mode: Option<BatchMode>,
+  #[serde(default)]
+  pub defaults: BatchDefaults,
+  pub projects: Vec<BatchItem>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchItemResult {
+  pub name: String,
+  pub project_dir: String,
+  // 未通过校验而未启动的条目为 None
+  pub job_id: Option<String>,
+  pub success: bool,
+  pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchReport {
+  pub id: String,
+  pub manifest: String,
+  pub mode: BatchMode,
+  pub started_at: u64,
+  pub finished_at: u64,
+  pub total: usize,
+  pub succeeded: usize,
+  pub failed: usize,
+  pub items: Vec<BatchItemResult>,
+  pub report_path: Option<String>,
+}
+
+// 按扩展名解析清单：.toml 为 TOML，其余按 JSON
+pub fn read_manifest(path: &str) -> Result<BatchManifest, String> {
+  let content = std::fs::read_to_string(path).map_err(|e| format!("读取清单失败：{}", e))?;
+  let is_toml = Path::new(path).extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("toml"));
+  if is_toml {
+    toml::from_str(&content).map_err(|e| format!("清单格式错误：{}", e))
+  } else {
+    serde_json::from_str(&content).map_err(|e| format!("清单格式错误：{}", e))
+  }
+}
+
+// 合并默认值并校验，得到单个项目的创建参数；优先级：条目字段 > 预设 > 清单默认值
+fn resolve_item(item: &BatchItem, defaults: &BatchDefaults, saved: &[Preset]) -> Result<CreateOptions, String> {
+  let preset = match item.preset.as_ref().or(defaults.preset.as_ref()) {
+    Some(name) => {
+      let p = saved.iter().find(|p| &p.name == name).ok_or_else(|| format!("未找到预设：{}", name))?;
+      p.validate()?;
+      Some(p)
+    }
+    None => None,
+  };
+  let pick = |v: &Option<String>, p: Option<String>, d: &Option<String>| {
+    v.clone().or(p).or_else(|| d.clone()).filter(|s| !s.trim().is_empty())
+  };
+  let name = item.name.trim().to_string();
+  if name.is_empty() { return Err("项目名称不能为空".into()); }
+  let directory = pick(&item.directory, preset.and_then(|p| p.directory.clone()), &defaults.directory)
+    .ok_or_else(|| "缺少存放目录 directory".to_string())?;
+  let version = pick(&item.version, preset.map(|p| p.version.clone()), &defaults.version).unwrap_or_else(|| "vue3".into());
+  let lang = pick(&item.lang, preset.map(|p| p.lang.clone()), &defaults.lang).unwrap_or_else(|| "ts".into());
+  if version != "vue3" && version != "vue2" { return Err(format!("不支持的 Vue 版本：{}", version)); }
+  if lang != "ts" && lang != "js" { return Err(format!("不支持的语言：{}", lang)); }
+  let features = item.features.clone()
+    .or_else(|| preset.map(|p| p.features.clone()))
+    .or_else(|| defaults.features.clone())
+    .unwrap_or_default();
+  let hooks = preset.map(|p| p.hooks.clone()).unwrap_or_default();
+  Ok(CreateOptions { version, lang, name, directory, features, hooks, create_vue: false })
+}
+
+fn write_report(app_handle: &tauri::AppHandle, report: &BatchReport) -> Result<String, String> {
+  let dir = app_handle.path().app_data_dir().map_err(|e| e.to_string())?.join("batch-reports");
+  std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+  let path = dir.join(format!("{}.json", report.id));
+  let content = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+  std::fs::write(&path, content).map_err(|e| e.to_string())?;
+  Ok(path.to_string_lossy().to_string())
+}
+
+const ABORTED: &str = "任务异常结束，未返回结果";
+
+fn run_batch(app_handle: &tauri::AppHandle, path: &str, manifest: BatchManifest, mode: BatchMode, saved: Vec<Preset>) -> BatchReport {
+  let started_at = job::now_millis();
+  let mut items: Vec<BatchItemResult> = Vec::new();
+  let mut seen: Vec<std::path::PathBuf> = Vec::new();
+  let (tx, rx) = mpsc::channel::<(usize, Result<(), String>)>();
+  let mut pending = 0;
+
+  for item in &manifest.projects {
+    let index = items.len();
+    let resolved = resolve_item(item, &manifest.defaults, &saved).and_then(|mut opts| {
+      project::resolve_features(app_handle, &mut opts)?;
+      let dir = opts.project_dir();
+      if seen.contains(&dir) { return Err("与清单中其他项目的目标目录重复".into()); }
+      seen.push(dir);
+      project::check_target_dir(opts.directory.clone(), opts.name.clone())?;
+      Ok(opts)
+    });
+    let opts = match resolved {
+      Ok(o) => o,
+      Err(e) => {
+        let project_dir = item.directory.as_deref().or(manifest.defaults.directory.as_deref()).map(|d| Path::new(d).join(&item.name).to_string_lossy().to_string()).unwrap_or_default();
+        items.push(BatchItemResult { name: item.name.clone(), project_dir, job_id: None, success: false, error: Some(e) });
+        continue;
+      }
+    };
+    let project_dir = opts.project_dir().to_string_lossy().to_string();
+    // 逐个模式每个项目单独一个通道，发送端只交给任务：任务未回调就被丢弃时 recv 立即返回错误而不是一直等待
+    let (item_tx, item_rx) = if mode == BatchMode::Sequential {
+      let (t, r) = mpsc::channel();
+      (t, Some(r))
+    } else {
+      (tx.clone(), None)
+    };
+    let on_finish = Box::new(move |r: &Result<(), String>| { let _ = item_tx.send((index, r.clone())); });
+    let job_id = project::spawn_create_then(app_handle.clone(), opts, false, "batch", None, false, on_finish);
+    items.push(BatchItemResult { name: item.name.clone(), project_dir, job_id: Some(job_id), success: false, error: None });
+    // 逐个模式：等待当前项目结束后再提交下一个
+    match item_rx {
+      Some(item_rx) => match item_rx.recv() {
+        Ok((i, r)) => apply_result(&mut items[i], r),
+        Err(_) => apply_result(&mut items[index], Err(ABORTED.into())),
+      },
+      None => pending += 1,
+    }
+  }
+  // 丢弃自身的发送端，所有任务结束（或被丢弃）后 recv 返回错误
+  drop(tx);
+  for _ in 0..pending {
+    match rx.recv() { Ok((i, r)) => apply_result(&mut items[i], r), Err(_) => break }
+  }
+  for item in items.iter_mut().filter(|i| i.job_id.is_some() && !i.success && i.error.is_none()) {
+    item.error = Some(ABORTED.into());
+  }
+
+  let succeeded = items.iter().filter(|i| i.success).count();
+  let mut report = BatchReport {
+    id: job::new_id(),
+    manifest: path.to_string(),
+    mode,
+    started_at,
+    finished_at: job::now_millis(),
+    total: items.len(),
+    succeeded,
+    failed: items.len() - succeeded,
+    items,
+    report_path: None,
+  };
+  match write_report(app_handle, &report) {
+    Ok(p) => report.report_path = Some(p),
+    Err(e) => log::warn!("写入批量创建报告失败：{}", e),
+  }
+  report
+}
+
+fn apply_result(item: &mut BatchItemResult, result: Result<(), String>) {
+  item.success = result.is_ok();
+  item.error = result.err();
+}
+
+// 按清单批量创建项目，全部结束后返回汇总报告（同时写入应用数据目录）；
+// mode 缺省时使用清单中的 mode，再缺省为逐个执行。各项目进度可通过任务列表查看
+#[tauri::command]
+pub async fn create_batch(app_handle: tauri::AppHandle, path: String, mode: Option<BatchMode>) -> Result<BatchReport, String> {
+  let manifest = read_manifest(&path)?;
+  if manifest.projects.is_empty() { return Err("清单中没有项目".into()); }
+  let mode = mode.or(manifest.mode).unwrap_or_default();
+  let saved = presets::load(&app_handle)?;
+  async_runtime::spawn_blocking(move || run_batch(&app_handle, &path, manifest, mode, saved))
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,190 @@
+use semver::Version;
+use serde::Serialize;
+
+use super::components::{self, Supported, VersionMap};
+use super::npm::{self, PackageMeta, VersionFilter, VersionMeta};
+use super::registry::load_package;
+
+// 根据 peerDependencies 推断组件支持的框架主版本，代替手工维护 supported / versions
+const VUE: &str = "vue";
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PeerCompat {
+  pub name: String,
+  // 声明的范围；未声明该 peer 时为 None
+  pub range: Option<String>,
+  pub optional: bool,
+  // 该范围覆盖的框架主版本（以框架已发布的正式版为准）
+  pub majors: Vec<u64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CompatReport {
+  pub key: String,
+  pub package: String,
+  // 实际检查的包版本
+  pub version: String,
+  pub peers: Vec<PeerCompat>,
+  // 按所检查版本推断的 Vue 2 / Vue 3 支持情况；未声明 vue peer 时无法判断（None）
+  pub vue2: Option<bool>,
+  pub vue3: Option<bool>,
+  pub current_supported: Supported,
+  pub current_versions: VersionMap,
+  // 任一正式版支持即视为支持该 Vue 主版本
+  pub proposed_supported: Supported,
+  // 各 Vue 主版本可用的最新正式版（写为 ^x.y.z）
+  pub proposed_versions: VersionMap,
+  pub changed: bool,
+  pub applied: bool,
+  // 使用了过期缓存等提示
+  pub warnings: Vec<String>,
+}
+
+fn stable_versions(meta: &PackageMeta) -> Vec<Version> {
+  meta
+    .versions
+    .iter()
+    .filter_map(|m| Version::parse(&m.version).ok())
+    .filter(|v| v.pre.is_empty())
+    .collect()
+}
+
+// 范围覆盖的框架主版本：存在满足范围的正式版即视为支持该主版本
+fn majors_for(range: &str, framework: &[Version]) -> Result<Vec<u64>, String> {
+  let reqs = npm::parse_range(range)?;
+  let mut majors: Vec<u64> = framework.iter().filter(|v| npm::satisfies(&reqs, v)).map(|v| v.major).collect();
+  majors.sort_unstable();
+  majors.dedup();
+  Ok(majors)
+}
+
+fn vue_majors(meta: &VersionMeta, vue: &[Version]) -> Option<Vec<u64>> {
+  meta.peer_dependencies.get(VUE).and_then(|r| majors_for(r, vue).ok())
+}
+
+// vue 已发布的正式版，用于判断 peer 范围覆盖的主版本
+pub async fn vue_versions(app_handle: &tauri::AppHandle, warnings: &mut Vec<String>) -> Result<Vec<Version>, String> {
+  let info = load_package(app_handle, VUE, false).await?;
+  if let Some(e) = &info.error { warnings.push(format!("{}：使用过期缓存（{}）", VUE, e)); }
+  Ok(stable_versions(&info.meta))
+}
+
+// 按 vue peer 推断的支持情况：vue2 / vue3 针对所检查的版本，lines 为各 Vue 主版本可用的最新正式版（未弃用）
+#[derive(Clone, Debug, Default)]
+pub struct VueSupport {
+  pub vue2: Option<bool>,
+  pub vue3: Option<bool>,
+  pub lines: VersionMap,
+}
+
+impl VueSupport {
+  // 所检查版本不支持时，较早的版本线（如兼容 Vue 2 的旧主版本）仍可使用
+  pub fn infer(meta: &PackageMeta, selected: &VersionMeta, vue: &[Version]) -> VueSupport {
+    let Some(majors) = vue_majors(selected, vue) else { return VueSupport::default() };
+    let stable = meta.list_versions(&VersionFilter::default()).unwrap_or_default();
+    let newest_for = |major: u64| {
+      stable.iter().find_map(|e| {
+        let m = meta.versions.iter().find(|m| m.version == e.version)?;
+        vue_majors(m, vue).filter(|ms| ms.contains(&major)).map(|_| format!("^{}", e.version))
+      })
+    };
+    VueSupport {
+      vue2: Some(majors.contains(&2)),
+      vue3: Some(majors.contains(&3)),
+      lines: VersionMap { vue2: newest_for(2), vue3: newest_for(3) },
+    }
+  }
+
+  // 写入建议：任一正式版支持即视为支持该 Vue 主版本；无法判断时保留原值
+  pub fn apply_to(&self, supported: &mut Supported, versions: &mut VersionMap) {
+    if let Some(v) = self.vue2 {
+      supported.vue2 = v || self.lines.vue2.is_some();
+      versions.vue2 = if supported.vue2 { self.lines.vue2.clone().or(versions.vue2.take()) } else { None };
+    }
+    if let Some(v) = self.vue3 {
+      supported.vue3 = v || self.lines.vue3.is_some();
+      versions.vue3 = if supported.vue3 { self.lines.vue3.clone().or(versions.vue3.take()) } else { None };
+    }
+  }
+}
+
+// 检查组件主包某个版本（默认 latest，可为版本号、范围或 dist-tag）对 vue 及 frameworks 中其他框架的 peer 要求，
+// 推断 supported 与各 Vue 主版本可用的最新版本；apply 为 true 时写回注册表
+#[tauri::command]
+pub async fn check_component_compat(
+  app_handle: tauri::AppHandle,
+  key: String,
+  version: Option<String>,
+  frameworks: Option<Vec<String>>,
+  apply: Option<bool>,
+) -> Result<CompatReport, String> {
+  let mut registry = components::read(&app_handle)?;
+  let component = registry.get(&key).cloned().ok_or_else(|| format!("未找到组件：{}", key))?;
+  let package = component.packages.first().cloned().ok_or_else(|| format!("组件 {} 未配置 packages", key))?;
+  let mut warnings: Vec<String> = Vec::new();
+
+  let info = load_package(&app_handle, &package, false).await?;
+  if let Some(e) = &info.error { warnings.push(format!("{}：使用过期缓存（{}）", package, e)); }
+  let range = version.filter(|v| !v.trim().is_empty()).unwrap_or_else(|| "latest".into());
+  let filter = VersionFilter { include_prerelease: true, include_deprecated: true, range: Some(range.clone()) };
+  let selected = info.meta.list_versions(&filter)?.into_iter().next().ok_or_else(|| format!("{} 没有满足 {} 的版本", package, range))?;
+  let meta = info.meta.versions.iter().find(|m| m.version == selected.version).cloned().ok_or_else(|| "版本信息缺失".to_string())?;
+
+  let mut names = vec![VUE.to_string()];
+  for f in frameworks.unwrap_or_default() {
+    let f = f.trim().to_string();
+    if !f.is_empty() && !names.contains(&f) { names.push(f); }
+  }
+  let vue = vue_versions(&app_handle, &mut warnings).await?;
+  let mut peers: Vec<PeerCompat> = Vec::new();
+  for name in names {
+    let range = meta.peer_dependencies.get(&name).cloned();
+    let optional = meta.optional_peers.contains(&name);
+    // 未声明 peer 的框架无需查询
+    let majors = match &range {
+      None => Vec::new(),
+      Some(r) if name == VUE => majors_for(r, &vue).unwrap_or_else(|e| { warnings.push(e); Vec::new() }),
+      Some(r) => {
+        let framework = load_package(&app_handle, &name, false).await?;
+        if let Some(e) = &framework.error { warnings.push(format!("{}：使用过期缓存（{}）", name, e)); }
+        majors_for(r, &stable_versions(&framework.meta)).unwrap_or_else(|e| { warnings.push(e); Vec::new() })
+      }
+    };
+    peers.push(PeerCompat { name, range, optional, majors });
+  }
+
+  let support = VueSupport::infer(&info.meta, &meta, &vue);
+  if support.vue2.is_none() {
+    warnings.push(format!("{}@{} 未声明 vue 的 peerDependencies，无法判断支持的 Vue 版本", package, meta.version));
+  }
+  let mut proposed_supported = component.supported.clone();
+  let mut proposed_versions = component.versions.clone();
+  support.apply_to(&mut proposed_supported, &mut proposed_versions);
+  let changed = proposed_supported != component.supported || proposed_versions != component.versions;
+
+  let mut applied = false;
+  if changed && apply.unwrap_or(false) {
+    if let Some(c) = registry.components.iter_mut().find(|c| c.key == key) {
+      c.supported = proposed_supported.clone();
+      c.versions = proposed_versions.clone();
+    }
+    components::save(&app_handle, registry)?;
+    applied = true;
+  }
+
+  Ok(CompatReport {
+    key,
+    package,
+    version: meta.version,
+    peers,
+    vue2: support.vue2,
+    vue3: support.vue3,
+    current_supported: component.supported,
+    current_versions: component.versions,
+    proposed_supported,
+    proposed_versions,
+    changed,
+    applied,
+    warnings,
+  })
+}
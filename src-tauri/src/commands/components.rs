@@ -0,0 +1,608 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::Manager;
+
+// components.json 的数据模型：带 schema 版本，读取时自动迁移旧格式，保存前逐字段校验
+pub const SCHEMA_VERSION: u32 = 2;
+
+// 各 Vue 版本对应的版本范围
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VersionMap {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub vue2: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub vue3: Option<String>,
+}
+
+impl VersionMap {
+  pub fn is_empty(&self) -> bool {
+    self.vue2.is_none() && self.vue3.is_none()
+  }
+
+  pub fn get(&self, version: &str) -> Option<&str> {
+    match version {
+      "vue2" => self.vue2.as_deref(),
+      "vue3" => self.vue3.as_deref(),
+      _ => None,
+    }
+  }
+}
+
+fn yes() -> bool {
+  true
+}
+
+// 支持的 Vue 版本与语言，缺省字段视为支持
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Supported {
+  #[serde(default = "yes")]
+  pub vue2: bool,
+  #[serde(default = "yes")]
+  pub vue3: bool,
+  #[serde(default = "yes")]
+  pub ts: bool,
+  #[serde(default = "yes")]
+  pub js: bool,
+}
+
+impl Default for Supported {
+  fn default() -> Self {
+    Supported { vue2: true, vue3: true, ts: true, js: true }
+  }
+}
+
+impl Supported {
+  pub fn allows(&self, version: &str, lang: &str) -> bool {
+    let vue_ok = if version == "vue3" { self.vue3 } else { self.vue2 };
+    let lang_ok = if lang == "ts" { self.ts } else { self.js };
+    vue_ok && lang_ok
+  }
+}
+
+// 组件分类；无法识别的分类归入 other
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+  Ui,
+  State,
+  Styling,
+  Icons,
+  Tooling,
+  Testing,
+  #[default]
+  #[serde(other)]
+  Other,
+}
+
+impl Category {
+  pub fn title(&self) -> &'static str {
+    match self {
+      Category::Ui => "UI 组件库",
+      Category::State => "状态管理",
+      Category::Styling => "样式",
+      Category::Icons => "图标",
+      Category::Tooling => "工具",
+      Category::Testing => "测试",
+      Category::Other => "其他",
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Component {
+  pub key: String,
+  // 允许为 null，normalize 时缺省为 key
+  #[serde(default, deserialize_with = "null_as_empty")]
+  pub label: String,
+  #[serde(default)]
+  pub packages: Vec<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub desc: Option<String>,
+  #[serde(default, skip_serializing_if = "VersionMap::is_empty")]
+  pub versions: VersionMap,
+  #[serde(default)]
+  pub supported: Supported,
+  #[serde(default)]
+  pub dev: bool,
+  // 依赖的其他组件 key，选择本组件时自动加入
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub requires: Vec<String>,
+  // 互斥的组件 key，不能同时选择
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub conflicts: Vec<String>,
+  #[serde(default)]
+  pub category: Category,
+  // 单选组：同组组件最多选择一个（如多个 UI 组件库）
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub group: Option<String>,
+  // 个人添加的条目：同步共享注册表时保留，不会被覆盖或删除
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub local: bool,
+  // 未识别的字段（更新版本的应用或团队自定义字段）原样保留，保存时写回
+  #[serde(flatten)]
+  pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+fn null_as_empty<'de, D: serde::Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+  Ok(Option::<String>::deserialize(d)?.unwrap_or_default())
+}
+
+impl Component {
+  // 当前版本与语言下需要安装的包（含版本范围）与 dev 标记；不支持时返回 None
+  pub fn packages_for(&self, version: &str, lang: &str) -> Option<(bool, Vec<String>)> {
+    if !self.supported.allows(version, lang) { return None; }
+    let ver = self.versions.get(version);
+    let pkgs = self.packages.iter().map(|name| match ver { Some(v) => format!("{}@{}", name, v), None => name.clone() }).collect();
+    Some((self.dev, pkgs))
+  }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ComponentRegistry {
+  #[serde(default)]
+  pub schema_version: u32,
+  #[serde(default)]
+  pub components: Vec<Component>,
+}
+
+impl Default for ComponentRegistry {
+  fn default() -> Self {
+    ComponentRegistry { schema_version: SCHEMA_VERSION, components: Vec::new() }
+  }
+}
+
+// 字段级校验错误，path 形如 components[2].packages[0]
+#[derive(Clone, Debug, Serialize)]
+pub struct FieldError {
+  pub path: String,
+  pub message: String,
+}
+
+// 内置组件的分类，用于为旧文件补全 category
+fn known_category(key: &str) -> Option<&'static str> {
+  match key {
+    "router" => Some("tooling"),
+    "pinia" => Some("state"),
+    "sass" | "vfonts" => Some("styling"),
+    "naive-ui" => Some("ui"),
+    "xicons" => Some("icons"),
+    _ => None,
+  }
+}
+
+// 将旧格式迁移到当前 schema，返回是否发生了变更
+// v0（无 schema_version）：单个 package / version 字段 -> packages / versions；根为数组时包一层 components。
+//   旧格式的 version 不区分 Vue 版本，安装时对 Vue 2 / Vue 3 都使用，因此同时写入 versions.vue2 与 versions.vue3
+// v1：为内置组件补全 category
+pub fn migrate(v: &mut serde_json::Value) -> bool {
+  if v.is_array() { *v = serde_json::json!({ "components": v.take() }); }
+  let Some(root) = v.as_object_mut() else { return false };
+  let version = root.get("schema_version").and_then(|x| x.as_u64()).unwrap_or(0);
+  if version >= SCHEMA_VERSION as u64 { return false; }
+  if let Some(items) = root.get_mut("components").and_then(|x| x.as_array_mut()) {
+    for item in items.iter_mut().filter_map(|i| i.as_object_mut()) {
+      if version < 2 && !item.contains_key("category") {
+        if let Some(cat) = item.get("key").and_then(|x| x.as_str()).and_then(known_category) {
+          item.insert("category".into(), serde_json::json!(cat));
+        }
+      }
+      if version >= 1 { continue; }
+      if let Some(pkg) = item.remove("package") {
+        let mut list: Vec<serde_json::Value> = item.get("packages").and_then(|x| x.as_array()).cloned().unwrap_or_default();
+        if pkg.is_string() && !list.contains(&pkg) { list.insert(0, pkg); }
+        item.insert("packages".into(), serde_json::Value::Array(list));
+      }
+      if let Some(ver) = item.remove("version") {
+        if ver.is_string() && !item.contains_key("versions") {
+          item.insert("versions".into(), serde_json::json!({ "vue2": ver.clone(), "vue3": ver }));
+        }
+      }
+    }
+  }
+  root.insert("schema_version".into(), serde_json::json!(SCHEMA_VERSION));
+  true
+}
+
+// 版本范围：npm 范围（如 "^3.x"、">=2.7 <3"）或 dist-tag 名称（如 "latest"、"legacy"）
+fn valid_range(range: &str) -> bool {
+  let range = range.trim();
+  let tag = range.starts_with(|c: char| c.is_ascii_alphabetic()) && range.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+  !range.is_empty() && (tag || super::npm::parse_range(range).is_ok())
+}
+
+impl ComponentRegistry {
+  // 由 JSON 构建（先迁移再解析）
+  pub fn from_value(mut v: serde_json::Value) -> Result<(Self, bool), String> {
+    let migrated = migrate(&mut v);
+    let reg: ComponentRegistry = serde_json::from_value(v).map_err(|e| format!("components.json 格式错误：{}", e))?;
+    Ok((reg, migrated))
+  }
+
+  pub fn get(&self, key: &str) -> Option<&Component> {
+    self.components.iter().find(|c| c.key == key)
+  }
+
+  // keys 连同其 requires 的传递闭包（按发现顺序，忽略注册表中不存在的 key）
+  pub fn with_requires(&self, keys: &[String]) -> Vec<String> {
+    let mut wanted: Vec<String> = Vec::new();
+    for k in keys {
+      if self.get(k).is_some() && !wanted.contains(k) { wanted.push(k.clone()); }
+    }
+    let mut i = 0;
+    while i < wanted.len() {
+      for r in self.get(&wanted[i]).map(|c| c.requires.clone()).unwrap_or_default() {
+        if self.get(&r).is_some() && !wanted.contains(&r) { wanted.push(r); }
+      }
+      i += 1;
+    }
+    wanted
+  }
+
+  // 补全可推导的字段（label 缺省为 key），去除包名首尾空白
+  pub fn normalize(&mut self) {
+    self.schema_version = SCHEMA_VERSION;
+    for c in self.components.iter_mut() {
+      c.key = c.key.trim().to_string();
+      if c.label.trim().is_empty() { c.label = c.key.clone(); }
+      c.group = c.group.take().map(|g| g.trim().to_string()).filter(|g| !g.is_empty());
+      for p in c.packages.iter_mut() { *p = p.trim().to_string(); }
+      // 界面中未填写的版本范围为空串，视为未指定
+      for v in [&mut c.versions.vue2, &mut c.versions.vue3] {
+        *v = v.take().map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+      }
+    }
+  }
+
+  pub fn validate(&self) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let mut err = |path: String, message: &str| errors.push(FieldError { path, message: message.to_string() });
+    for (i, c) in self.components.iter().enumerate() {
+      let at = |field: &str| format!("components[{}].{}", i, field);
+      if c.key.trim().is_empty() {
+        err(at("key"), "不能为空");
+      } else if c.key.chars().any(char::is_whitespace) {
+        err(at("key"), "不能包含空白字符");
+      } else if self.components[..i].iter().any(|o| o.key == c.key) {
+        err(at("key"), &format!("与前面的组件重复：{}", c.key));
+      }
+      if c.packages.is_empty() { err(at("packages"), "至少需要一个包"); }
+      for (j, p) in c.packages.iter().enumerate() {
+        let path = format!("components[{}].packages[{}]", i, j);
+        if p.trim().is_empty() {
+          err(path, "包名不能为空");
+        } else if p.chars().any(char::is_whitespace) {
+          err(path, "包名不能包含空白字符");
+        } else if p.rfind('@').is_some_and(|pos| pos > 0) {
+          err(path, "包名中不能带版本，版本范围请写在 versions 中");
+        } else if p.starts_with('@') && !p.contains('/') {
+          err(path, "作用域包名应为 @scope/name 形式");
+        }
+      }
+      for (field, v) in [("vue2", &c.versions.vue2), ("vue3", &c.versions.vue3)] {
+        if let Some(range) = v.as_deref().filter(|s| !valid_range(s)) { err(at(&format!("versions.{}", field)), &format!("版本范围无效：{}", range)); }
+      }
+      for (field, keys) in [("requires", &c.requires), ("conflicts", &c.conflicts)] {
+        for (j, k) in keys.iter().enumerate() {
+          let path = format!("components[{}].{}[{}]", i, field, j);
+          if k == &c.key {
+            err(path, "不能引用自身");
+          } else if self.get(k).is_none() {
+            err(path, &format!("未在注册表中找到组件：{}", k));
+          } else if field == "conflicts" && c.requires.contains(k) {
+            err(path, &format!("{} 不能同时出现在 requires 与 conflicts 中", k));
+          }
+        }
+      }
+      if c.group.as_deref().is_some_and(|g| g.trim().is_empty()) { err(at("group"), "单选组名称不能为空"); }
+      if !c.supported.vue2 && !c.supported.vue3 { err(at("supported"), "至少支持一个 Vue 版本"); }
+      if !c.supported.ts && !c.supported.js { err(at("supported"), "至少支持一种语言"); }
+    }
+    errors
+  }
+}
+
+// 因 requires 自动加入的组件
+#[derive(Clone, Debug, Serialize)]
+pub struct AddedFeature {
+  pub key: String,
+  pub required_by: String,
+}
+
+// 特性解析结果：features 为最终安装顺序（依赖在前）
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct FeatureResolution {
+  pub features: Vec<String>,
+  pub added: Vec<AddedFeature>,
+  // 所选组件不支持当前版本或语言，创建时不会安装
+  pub skipped: Vec<String>,
+  pub errors: Vec<String>,
+}
+
+impl FeatureResolution {
+  pub fn is_ok(&self) -> bool {
+    self.errors.is_empty()
+  }
+}
+
+impl ComponentRegistry {
+  // 展开 requires 并检查 conflicts：依赖按深度优先排在依赖方之前，循环依赖只展开一次
+  pub fn resolve_features(&self, selected: &[String], version: &str, lang: &str) -> FeatureResolution {
+    let mut res = FeatureResolution::default();
+    // key -> 引入者（None 表示用户直接选择）
+    let mut origin: Vec<(String, Option<String>)> = Vec::new();
+    fn visit(reg: &ComponentRegistry, key: &str, by: Option<&str>, version: &str, lang: &str, origin: &mut Vec<(String, Option<String>)>, res: &mut FeatureResolution) {
+      if origin.iter().any(|(k, _)| k == key) { return; }
+      let Some(c) = reg.get(key) else {
+        match by {
+          Some(b) => res.errors.push(format!("{} 依赖的组件 {} 未在注册表中找到", b, key)),
+          None => res.errors.push(format!("未在注册表中找到组件：{}", key)),
+        }
+        return;
+      };
+      if !c.supported.allows(version, lang) {
+        match by {
+          Some(b) => res.errors.push(format!("{} 依赖 {}，但 {} 不支持 {} + {}", b, key, key, version, lang)),
+          None => res.skipped.push(key.to_string()),
+        }
+        return;
+      }
+      origin.push((key.to_string(), by.map(String::from)));
+      for r in c.requires.iter() { visit(reg, r, Some(key), version, lang, origin, res); }
+      res.features.push(key.to_string());
+      if let Some(b) = by { res.added.push(AddedFeature { key: key.to_string(), required_by: b.to_string() }); }
+    }
+    for key in selected {
+      // 已作为依赖加入的组件再被直接选择时，不再视为自动加入
+      res.added.retain(|a| &a.key != key);
+      if let Some(o) = origin.iter_mut().find(|(k, _)| k == key) { o.1 = None; }
+      visit(self, key, None, version, lang, &mut origin, &mut res);
+    }
+    let describe = |key: &str| match origin.iter().find(|(k, _)| k == key).and_then(|(_, by)| by.clone()) {
+      Some(by) => format!("{}（由 {} 引入）", key, by),
+      None => key.to_string(),
+    };
+    let mut groups: Vec<(&str, Vec<String>)> = Vec::new();
+    for key in res.features.iter() {
+      let Some(group) = self.get(key).and_then(|c| c.group.as_deref()) else { continue };
+      match groups.iter_mut().find(|(g, _)| *g == group) {
+        Some((_, members)) => members.push(describe(key)),
+        None => groups.push((group, vec![describe(key)])),
+      }
+    }
+    for (group, members) in groups.iter().filter(|(_, m)| m.len() > 1) {
+      res.errors.push(format!("单选组 {} 只能选择一个：{}", group, members.join("、")));
+    }
+    for (i, a) in res.features.iter().enumerate() {
+      for b in res.features[i + 1..].iter() {
+        let declared = |x: &str, y: &str| self.get(x).is_some_and(|c| c.conflicts.iter().any(|k| k == y));
+        if declared(a, b) || declared(b, a) {
+          res.errors.push(format!("{} 与 {} 互斥，不能同时选择", describe(a), describe(b)));
+        }
+      }
+    }
+    res
+  }
+}
+
+// 按分类分组的注册表视图
+#[derive(Clone, Debug, Serialize)]
+pub struct CategoryGroup {
+  pub category: Category,
+  pub title: String,
+  pub components: Vec<Component>,
+}
+
+impl ComponentRegistry {
+  // 按分类顺序分组；组内先按单选组、再按名称排序，同一单选组的组件相邻
+  pub fn grouped(&self) -> Vec<CategoryGroup> {
+    let mut out: Vec<CategoryGroup> = Vec::new();
+    let mut sorted = self.components.clone();
+    sorted.sort_by(|a, b| {
+      (a.category, a.group.is_none(), &a.group, a.label.to_lowercase())
+        .cmp(&(b.category, b.group.is_none(), &b.group, b.label.to_lowercase()))
+    });
+    for c in sorted {
+      match out.last_mut() {
+        Some(g) if g.category == c.category => g.components.push(c),
+        _ => out.push(CategoryGroup { category: c.category, title: c.category.title().into(), components: vec![c] }),
+      }
+    }
+    out
+  }
+}
+
+pub fn format_errors(errors: &[FieldError]) -> String {
+  errors.iter().map(|e| format!("{}：{}", e.path, e.message)).collect::<Vec<_>>().join("\n")
+}
+
+pub fn registry_path(app_handle: &tauri::AppHandle) -> Option<PathBuf> {
+  app_handle.path().app_config_dir().ok().map(|mut p| { p.push("components.json"); p })
+}
+
+// 默认注册表条目：支持全部版本与语言、无版本范围，其余字段按需覆盖
+fn component(key: &str, label: &str, packages: &[&str], desc: &str, category: Category) -> Component {
+  Component {
+    key: key.into(),
+    label: label.into(),
+    packages: packages.iter().map(|s| s.to_string()).collect(),
+    desc: Some(desc.into()),
+    versions: VersionMap::default(),
+    supported: Supported::default(),
+    dev: false,
+    requires: Vec::new(),
+    conflicts: Vec::new(),
+    category,
+    group: None,
+    local: false,
+    extra: serde_json::Map::new(),
+  }
+}
+
+// 首次使用时写入的默认注册表
+pub fn default_registry() -> ComponentRegistry {
+  let ver = |vue2: Option<&str>, vue3: Option<&str>| VersionMap { vue2: vue2.map(String::from), vue3: vue3.map(String::from) };
+  ComponentRegistry {
+    schema_version: SCHEMA_VERSION,
+    components: vec![
+      Component { versions: ver(Some("^3.x"), Some("^4.6.3")), ..component("router", "vue-router", &["vue-router"], "路由管理", Category::Tooling) },
+      Component { versions: ver(None, Some("^2.3.1")), ..component("pinia", "pinia", &["pinia", "pinia-plugin-persistedstate"], "轻量状态管理", Category::State) },
+      Component {
+        versions: ver(Some("^1.94.0"), Some("^1.94.0")),
+        dev: true,
+        ..component("sass", "sass", &["sass", "sass-loader"], "CSS 预处理器", Category::Styling)
+      },
+      Component {
+        supported: Supported { vue2: false, vue3: true, ts: true, js: false },
+        group: Some("ui-library".into()),
+        ..component("naive-ui", "naive-ui", &["naive-ui"], "Vue 3 组件库", Category::Ui)
+      },
+      component("vfonts", "vfonts", &["vfonts"], "网页与代码字体", Category::Styling),
+      component("xicons", "@vicons/ionicons5", &["@vicons/ionicons5"], "图标库", Category::Icons),
+    ],
+  }
+}
+
+fn write(app_handle: &tauri::AppHandle, registry: &ComponentRegistry) -> Result<(), String> {
+  let path = registry_path(app_handle).ok_or_else(|| "config dir not available".to_string())?;
+  if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+  let content = serde_json::to_string_pretty(registry).map_err(|e| e.to_string())?;
+  std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+// 读取注册表：不存在时写入默认内容；旧格式迁移后回写（原文件备份为 components.json.bak）
+pub fn read(app_handle: &tauri::AppHandle) -> Result<ComponentRegistry, String> {
+  let path = registry_path(app_handle).ok_or_else(|| "config dir not available".to_string())?;
+  if !path.exists() {
+    let reg = default_registry();
+    write(app_handle, &reg)?;
+    return Ok(reg);
+  }
+  let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+  let v: serde_json::Value = serde_json::from_str(&content).map_err(|e| format!("components.json 格式错误：{}", e))?;
+  let (reg, migrated) = ComponentRegistry::from_value(v)?;
+  if migrated {
+    let _ = std::fs::write(path.with_extension("json.bak"), &content);
+    if let Err(e) = write(app_handle, &reg) { log::warn!("回写迁移后的 components.json 失败：{}", e); }
+  }
+  Ok(reg)
+}
+
+// 读取失败（如文件损坏）时回退为空注册表，供创建流程使用
+pub fn load(app_handle: &tauri::AppHandle) -> ComponentRegistry {
+  read(app_handle).unwrap_or_else(|e| { log::warn!("读取组件注册表失败：{}", e); ComponentRegistry::default() })
+}
+
+// 规范化并校验后保存；存在错误时拒绝保存并逐条列出字段错误
+pub fn save(app_handle: &tauri::AppHandle, mut registry: ComponentRegistry) -> Result<(), String> {
+  registry.normalize();
+  let errors = registry.validate();
+  if !errors.is_empty() { return Err(format!("组件注册表校验失败：\n{}", format_errors(&errors))); }
+  write(app_handle, &registry)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn keeps_unknown_fields_and_accepts_null_label() {
+    let value = serde_json::json!([{ "key": "demo", "label": null, "package": "demo-ui", "version": "^1.0.0", "owner": "team-a", "meta": { "since": 2 } }]);
+    let (mut reg, migrated) = ComponentRegistry::from_value(value).unwrap();
+    assert!(migrated);
+    reg.normalize();
+    let c = reg.get("demo").unwrap();
+    assert_eq!(c.label, "demo");
+    assert_eq!(c.packages, vec!["demo-ui"]);
+    assert_eq!(c.versions, VersionMap { vue2: Some("^1.0.0".into()), vue3: Some("^1.0.0".into()) });
+    let out = serde_json::to_value(c).unwrap();
+    assert_eq!(out["owner"], "team-a");
+    assert_eq!(out["meta"]["since"], 2);
+    assert!(out.get("package").is_none() && out.get("version").is_none());
+  }
+
+  #[test]
+  fn blank_versions_are_unset_before_validation() {
+    // 界面新增条目或清空版本时提交的内容
+    let value = serde_json::json!({ "schema_version": SCHEMA_VERSION, "components": [
+      { "key": "demo", "label": "demo", "packages": ["demo-ui"], "versions": { "vue2": "", "vue3": "  " } },
+      { "key": "tagged", "label": "tagged", "packages": ["tagged-ui"], "versions": { "vue2": "legacy", "vue3": "^2.x || >=3.1 <4" } },
+    ] });
+    let (mut reg, _) = ComponentRegistry::from_value(value).unwrap();
+    reg.normalize();
+    assert!(reg.validate().is_empty(), "{:?}", reg.validate());
+    assert_eq!(reg.get("demo").unwrap().versions, VersionMap::default());
+    reg.components[0].versions.vue3 = Some(">=> 1".into());
+    let errors = reg.validate();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path, "components[0].versions.vue3");
+  }
+
+  fn registry() -> ComponentRegistry {
+    let value = serde_json::json!({ "schema_version": SCHEMA_VERSION, "components": [
+      { "key": "persist", "packages": ["pinia-plugin-persistedstate"], "requires": ["pinia"] },
+      { "key": "pinia", "packages": ["pinia"] },
+      { "key": "legacy", "packages": ["legacy-plugin"], "requires": ["vue2-only"] },
+      { "key": "vue2-only", "packages": ["vue2-only"], "supported": { "vue3": false } },
+      { "key": "ts-only", "packages": ["ts-only"], "supported": { "js": false } },
+      { "key": "needs-ts", "packages": ["needs-ts"], "requires": ["ts-only"] },
+      { "key": "sass", "packages": ["sass"], "conflicts": ["less"] },
+      { "key": "less", "packages": ["less"] },
+      { "key": "element-plus", "packages": ["element-plus"], "group": "ui-library" },
+      { "key": "naive-ui", "packages": ["naive-ui"], "group": "ui-library" },
+    ] });
+    ComponentRegistry::from_value(value).unwrap().0
+  }
+
+  fn keys(list: &[&str]) -> Vec<String> {
+    list.iter().map(|k| k.to_string()).collect()
+  }
+
+  #[test]
+  fn resolve_adds_required_dependencies_first() {
+    let res = registry().resolve_features(&keys(&["persist"]), "vue3", "ts");
+    assert!(res.is_ok(), "{:?}", res.errors);
+    assert_eq!(res.features, vec!["pinia", "persist"]);
+    assert_eq!(res.added.len(), 1);
+    assert_eq!((res.added[0].key.as_str(), res.added[0].required_by.as_str()), ("pinia", "persist"));
+    // 直接选择的依赖不算自动加入
+    let res = registry().resolve_features(&keys(&["pinia", "persist"]), "vue3", "ts");
+    assert_eq!(res.features, vec!["pinia", "persist"]);
+    assert!(res.added.is_empty());
+  }
+
+  #[test]
+  fn resolve_rejects_unsupported_requirement() {
+    let res = registry().resolve_features(&keys(&["legacy"]), "vue3", "ts");
+    assert_eq!(res.errors.len(), 1);
+    assert!(res.errors[0].contains("legacy 依赖 vue2-only"), "{}", res.errors[0]);
+    let res = registry().resolve_features(&keys(&["needs-ts"]), "vue3", "js");
+    assert_eq!(res.errors.len(), 1);
+    assert!(res.errors[0].contains("ts-only 不支持 vue3 + js"), "{}", res.errors[0]);
+    // 同样的组合下支持时正常加入
+    assert!(registry().resolve_features(&keys(&["legacy"]), "vue2", "js").is_ok());
+    // 直接选择但不支持的组件跳过而非报错
+    let res = registry().resolve_features(&keys(&["vue2-only"]), "vue3", "ts");
+    assert!(res.is_ok());
+    assert_eq!(res.skipped, vec!["vue2-only"]);
+  }
+
+  #[test]
+  fn resolve_rejects_declared_conflicts() {
+    let res = registry().resolve_features(&keys(&["less", "sass"]), "vue3", "ts");
+    assert_eq!(res.errors.len(), 1);
+    assert!(res.errors[0].contains("互斥"), "{}", res.errors[0]);
+  }
+
+  #[test]
+  fn resolve_rejects_two_picks_from_one_group() {
+    let res = registry().resolve_features(&keys(&["element-plus", "naive-ui"]), "vue3", "ts");
+    assert_eq!(res.errors.len(), 1);
+    assert!(res.errors[0].contains("单选组 ui-library"), "{}", res.errors[0]);
+    assert!(registry().resolve_features(&keys(&["element-plus", "pinia"]), "vue3", "ts").is_ok());
+  }
+
+  #[test]
+  fn default_registry_is_valid() {
+    assert!(default_registry().validate().is_empty());
+  }
+}
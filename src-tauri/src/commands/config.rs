@@ -1,3 +1,6 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::process::Command;
 use tauri::Manager;
 
 #[tauri::command]
@@ -8,4 +11,342 @@ pub fn read_default_directory(app_handle: tauri::AppHandle) -> Result<Option<Str
   let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
   let v: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
   Ok(v.get("default_directory").and_then(|x| x.as_str()).map(|s| s.to_string()))
-}
\ No newline at end of file
+}
+
+// 持久化默认目录（创建项目时勾选“设为默认”）
+pub fn write_default_directory(app_handle: &tauri::AppHandle, directory: &str) -> Result<(), String> {
+  if let Ok(mut cfg_dir) = app_handle.path().app_config_dir() {
+    std::fs::create_dir_all(&cfg_dir).map_err(|e| e.to_string())?;
+    cfg_dir.push("config.json");
+    let payload = serde_json::json!({ "default_directory": directory });
+    std::fs::write(&cfg_dir, payload.to_string()).map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
+
+// 单个步骤的执行策略：超时秒数（0 表示不限制）、失败重试次数、重试退避基数（毫秒，逐次翻倍）
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StepPolicy {
+  pub timeout_secs: u64,
+  pub retries: u32,
+  pub backoff_ms: u64,
+}
+
+impl Default for StepPolicy {
+  fn default() -> Self {
+    StepPolicy { timeout_secs: 300, retries: 2, backoff_ms: 2000 }
+  }
+}
+
+// 设置文件中可能只写了部分字段，缺省字段取各步骤自己的默认值
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct PartialPolicy {
+  timeout_secs: Option<u64>,
+  retries: Option<u32>,
+  backoff_ms: Option<u64>,
+}
+
+fn policy_or<'de, D: Deserializer<'de>>(d: D, default: StepPolicy) -> Result<StepPolicy, D::Error> {
+  let p = PartialPolicy::deserialize(d)?;
+  Ok(StepPolicy {
+    timeout_secs: p.timeout_secs.unwrap_or(default.timeout_secs),
+    retries: p.retries.unwrap_or(default.retries),
+    backoff_ms: p.backoff_ms.unwrap_or(default.backoff_ms),
+  })
+}
+
+fn scaffold_policy() -> StepPolicy { StepPolicy { timeout_secs: 300, retries: 2, backoff_ms: 2000 } }
+fn install_policy() -> StepPolicy { StepPolicy { timeout_secs: 600, retries: 2, backoff_ms: 3000 } }
+fn add_policy() -> StepPolicy { StepPolicy { timeout_secs: 180, retries: 2, backoff_ms: 2000 } }
+fn view_policy() -> StepPolicy { StepPolicy { timeout_secs: 8, retries: 0, backoff_ms: 0 } }
+
+fn de_scaffold<'de, D: Deserializer<'de>>(d: D) -> Result<StepPolicy, D::Error> { policy_or(d, scaffold_policy()) }
+fn de_install<'de, D: Deserializer<'de>>(d: D) -> Result<StepPolicy, D::Error> { policy_or(d, install_policy()) }
+fn de_add<'de, D: Deserializer<'de>>(d: D) -> Result<StepPolicy, D::Error> { policy_or(d, add_policy()) }
+fn de_view<'de, D: Deserializer<'de>>(d: D) -> Result<StepPolicy, D::Error> { policy_or(d, view_policy()) }
+
+// 各类 pnpm 调用的策略：脚手架（dlx）、依赖安装、追加依赖、版本查询（view）
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StepPolicies {
+  #[serde(default = "scaffold_policy", deserialize_with = "de_scaffold")]
+  pub scaffold: StepPolicy,
+  #[serde(default = "install_policy", deserialize_with = "de_install")]
+  pub install: StepPolicy,
+  #[serde(default = "add_policy", deserialize_with = "de_add")]
+  pub add: StepPolicy,
+  #[serde(default = "view_policy", deserialize_with = "de_view")]
+  pub view: StepPolicy,
+}
+
+impl Default for StepPolicies {
+  fn default() -> Self {
+    StepPolicies { scaffold: scaffold_policy(), install: install_policy(), add: add_policy(), view: view_policy() }
+  }
+}
+
+// 网络设置：npm 镜像、作用域镜像（如 "@corp" -> URL）与 HTTP(S) 代理，统一作用于所有 pnpm 调用
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+  pub registry: Option<String>,
+  pub scoped_registries: BTreeMap<String, String>,
+  pub http_proxy: Option<String>,
+  pub https_proxy: Option<String>,
+  pub no_proxy: Option<String>,
+  // 默认 registry 的访问令牌（私有镜像），不会写入项目 .npmrc
+  pub auth_token: Option<String>,
+  // 是否将镜像配置写入生成项目的 .npmrc
+  pub write_npmrc: bool,
+}
+
+fn non_empty(v: &Option<String>) -> Option<&str> {
+  v.as_deref().map(|s| s.trim()).filter(|s| !s.is_empty())
+}
+
+// 作用域统一为 "@scope" 形式
+fn normalize_scope(scope: &str) -> String {
+  let s = scope.trim();
+  if s.starts_with('@') { s.to_string() } else { format!("@{}", s) }
+}
+
+// .npmrc 中令牌的键：去掉协议，保留 "//host/path/" 形式
+fn registry_auth_key(url: &str) -> String {
+  let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+  format!("//{}/", rest.trim_end_matches('/'))
+}
+
+impl NetworkSettings {
+  pub fn registry_url(&self) -> Option<&str> {
+    non_empty(&self.registry)
+  }
+
+  // 包实际使用的 registry：作用域包优先使用对应的作用域镜像
+  pub fn registry_for(&self, package: &str) -> Option<&str> {
+    let scoped = package.split_once('/').filter(|(s, _)| s.starts_with('@')).and_then(|(scope, _)| {
+      self.scoped_registries.iter().find(|(k, v)| normalize_scope(k) == scope && !v.trim().is_empty()).map(|(_, v)| v.trim())
+    });
+    scoped.or_else(|| self.registry_url())
+  }
+
+  // 访问指定 registry 使用的令牌：仅发送给默认 registry
+  pub fn auth_token_for(&self, registry: &str) -> Option<&str> {
+    let default = self.registry_url()?;
+    if default.trim_end_matches('/') != registry.trim_end_matches('/') { return None; }
+    non_empty(&self.auth_token)
+  }
+
+  // 按目标地址选择代理：命中 no_proxy（逗号分隔的主机或域名后缀，"*" 表示全部）时不使用代理
+  pub fn proxy_for(&self, url: &str) -> Option<&str> {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let host = rest.split(['/', ':']).next().unwrap_or("").to_ascii_lowercase();
+    let bypass = non_empty(&self.no_proxy).is_some_and(|list| {
+      list.split(',').map(|s| s.trim().trim_start_matches('.').to_ascii_lowercase()).filter(|s| !s.is_empty()).any(|s| {
+        s == "*" || host == s || host.ends_with(&format!(".{}", s))
+      })
+    });
+    if bypass { return None; }
+    if url.starts_with("https://") { non_empty(&self.https_proxy).or(non_empty(&self.http_proxy)) } else { non_empty(&self.http_proxy) }
+  }
+
+  // 以 npm_config_* 环境变量注入：子进程（如 Vue CLI 内部再调用 pnpm install）也能继承
+  pub fn apply_to(&self, cmd: &mut Command) {
+    if let Some(r) = self.registry_url() { cmd.env("npm_config_registry", r); }
+    for (scope, url) in self.scoped_registries.iter() {
+      if url.trim().is_empty() { continue; }
+      cmd.env(format!("npm_config_{}:registry", normalize_scope(scope)), url.trim());
+    }
+    if let Some(p) = non_empty(&self.http_proxy) {
+      cmd.env("npm_config_proxy", p).env("HTTP_PROXY", p).env("http_proxy", p);
+    }
+    if let Some(p) = non_empty(&self.https_proxy) {
+      cmd.env("npm_config_https_proxy", p).env("HTTPS_PROXY", p).env("https_proxy", p);
+    }
+    if let Some(n) = non_empty(&self.no_proxy) {
+      cmd.env("npm_config_noproxy", n).env("NO_PROXY", n).env("no_proxy", n);
+    }
+    if let (Some(r), Some(t)) = (self.registry_url(), non_empty(&self.auth_token)) {
+      cmd.env(format!("npm_config_{}:_authToken", registry_auth_key(r)), t);
+    }
+  }
+
+  // 生成项目 .npmrc 内容；代理属于本机环境，不写入项目
+  pub fn npmrc_content(&self) -> Option<String> {
+    let mut lines: Vec<String> = Vec::new();
+    if let Some(r) = self.registry_url() { lines.push(format!("registry={}", r)); }
+    for (scope, url) in self.scoped_registries.iter() {
+      if url.trim().is_empty() { continue; }
+      lines.push(format!("{}:registry={}", normalize_scope(scope), url.trim()));
+    }
+    if lines.is_empty() { None } else { Some(lines.join("\n") + "\n") }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistrySourceKind {
+  #[default]
+  Path,
+  Git,
+  Url,
+}
+
+// 共享组件注册表的来源：本地文件或目录、本地 git 仓库（同步前 pull）、HTTP 地址
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegistrySource {
+  pub kind: RegistrySourceKind,
+  pub location: String,
+  // 目录或仓库内的文件路径，缺省为 components.json
+  pub file: Option<String>,
+}
+
+// 应用设置（settings.json），缺省字段使用默认值
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+  pub steps: StepPolicies,
+  pub network: NetworkSettings,
+  // 离线模式：脚手架使用内置模板生成，依赖安装使用 pnpm --offline（需预先预热 store）
+  pub offline: bool,
+  // 复用缓存的脚手架输出，跳过重复的 pnpm dlx
+  pub scaffold_cache: bool,
+  // 任务日志保留天数，清理时删除更早的日志；0 表示永久保留
+  pub log_retention_days: u32,
+  // 同时执行的创建任务数，其余任务排队；多个 pnpm 同时写 store 容易冲突，默认逐个执行
+  pub max_concurrent_jobs: usize,
+  // npm 版本查询结果的缓存有效期（秒），0 表示每次都重新查询（失败时仍使用旧缓存）
+  pub npm_cache_ttl_secs: u64,
+  // 团队共享的组件注册表来源，未配置时不同步
+  pub registry_source: Option<RegistrySource>,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Settings {
+      steps: StepPolicies::default(),
+      network: NetworkSettings::default(),
+      offline: false,
+      scaffold_cache: true,
+      log_retention_days: 30,
+      max_concurrent_jobs: 1,
+      npm_cache_ttl_secs: 3600,
+      registry_source: None,
+    }
+  }
+}
+
+fn settings_path(app_handle: &tauri::AppHandle) -> Option<std::path::PathBuf> {
+  app_handle.path().app_config_dir().ok().map(|mut p| { p.push("settings.json"); p })
+}
+
+// 读取设置文件；不存在时为默认值，读取或解析失败时报错
+pub fn read_settings_file(app_handle: &tauri::AppHandle) -> Result<Settings, String> {
+  let path = settings_path(app_handle).ok_or_else(|| "config dir not available".to_string())?;
+  if !path.exists() { return Ok(Settings::default()); }
+  let content = std::fs::read_to_string(&path).map_err(|e| format!("读取 settings.json 失败：{}", e))?;
+  serde_json::from_str(&content).map_err(|e| format!("settings.json 格式错误（{}）：{}", path.display(), e))
+}
+
+// 供后台流程使用：读取失败时记录错误并回退为默认值，界面经 read_settings 获得具体错误
+pub fn load_settings(app_handle: &tauri::AppHandle) -> Settings {
+  read_settings_file(app_handle).unwrap_or_else(|e| { log::error!("{}，使用默认设置", e); Settings::default() })
+}
+
+#[tauri::command]
+pub fn read_settings(app_handle: tauri::AppHandle) -> Result<Settings, String> {
+  read_settings_file(&app_handle)
+}
+
+#[tauri::command]
+pub fn save_settings(app_handle: tauri::AppHandle, payload: Settings) -> Result<(), String> {
+  let path = settings_path(&app_handle).ok_or_else(|| "config dir not available".to_string())?;
+  if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+  let content = serde_json::to_string_pretty(&payload).map_err(|e| e.to_string())?;
+  std::fs::write(&path, content).map_err(|e| e.to_string())?;
+  // 调大并发数后立即启动排队中的任务
+  super::queue::pump(&app_handle);
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn network() -> NetworkSettings {
+    let mut scoped = BTreeMap::new();
+    scoped.insert("corp".to_string(), " https://npm.corp.example/ ".to_string());
+    scoped.insert("@empty".to_string(), "  ".to_string());
+    NetworkSettings {
+      registry: Some("https://registry.npmmirror.com/".into()),
+      scoped_registries: scoped,
+      http_proxy: Some("http://proxy:8080".into()),
+      https_proxy: Some(" ".into()),
+      no_proxy: Some("localhost, .corp.example,10.0.0.1".into()),
+      auth_token: Some("secret".into()),
+      write_npmrc: true,
+    }
+  }
+
+  fn envs(cmd: &Command) -> BTreeMap<String, Option<String>> {
+    cmd.get_envs().map(|(k, v)| (k.to_string_lossy().to_string(), v.map(|v| v.to_string_lossy().to_string()))).collect()
+  }
+
+  #[test]
+  fn apply_to_injects_npm_config_env() {
+    let mut cmd = Command::new("pnpm");
+    network().apply_to(&mut cmd);
+    let envs = envs(&cmd);
+    let get = |k: &str| envs.get(k).cloned().flatten();
+    assert_eq!(get("npm_config_registry").as_deref(), Some("https://registry.npmmirror.com/"));
+    assert_eq!(get("npm_config_@corp:registry").as_deref(), Some("https://npm.corp.example/"));
+    assert_eq!(get("npm_config_proxy").as_deref(), Some("http://proxy:8080"));
+    assert_eq!(get("HTTP_PROXY").as_deref(), Some("http://proxy:8080"));
+    assert_eq!(get("npm_config_noproxy").as_deref(), Some("localhost, .corp.example,10.0.0.1"));
+    assert_eq!(get("npm_config_//registry.npmmirror.com/:_authToken").as_deref(), Some("secret"));
+    // 空值不注入
+    assert!(!envs.contains_key("npm_config_@empty:registry"));
+    assert!(!envs.contains_key("npm_config_https_proxy"));
+    assert!(!envs.contains_key("HTTPS_PROXY"));
+  }
+
+  #[test]
+  fn apply_to_without_settings_leaves_env_untouched() {
+    let mut cmd = Command::new("pnpm");
+    NetworkSettings { auth_token: Some("secret".into()), ..Default::default() }.apply_to(&mut cmd);
+    // 没有默认 registry 时令牌也不注入
+    assert_eq!(cmd.get_envs().count(), 0);
+  }
+
+  #[test]
+  fn registry_for_matches_scope() {
+    let net = network();
+    assert_eq!(net.registry_for("@corp/ui"), Some("https://npm.corp.example/"));
+    // 作用域需完全一致
+    assert_eq!(net.registry_for("@corporate/ui"), Some("https://registry.npmmirror.com/"));
+    assert_eq!(net.registry_for("corp/ui"), Some("https://registry.npmmirror.com/"));
+    // 作用域镜像为空时回退到默认 registry
+    assert_eq!(net.registry_for("@empty/ui"), Some("https://registry.npmmirror.com/"));
+    assert_eq!(net.registry_for("vue"), Some("https://registry.npmmirror.com/"));
+    assert_eq!(NetworkSettings::default().registry_for("@corp/ui"), None);
+  }
+
+  #[test]
+  fn proxy_for_honours_no_proxy() {
+    let net = network();
+    assert_eq!(net.proxy_for("http://localhost:4873/vue"), None);
+    assert_eq!(net.proxy_for("https://npm.corp.example/ui"), None);
+    assert_eq!(net.proxy_for("https://a.npm.corp.example"), None);
+    assert_eq!(net.proxy_for("http://10.0.0.1/x"), None);
+    // 仅后缀相同但不是子域名时仍走代理
+    assert_eq!(net.proxy_for("https://notcorp.example/ui"), Some("http://proxy:8080"));
+    // https 代理为空时回退到 http 代理
+    assert_eq!(net.proxy_for("https://registry.npmjs.org/vue"), Some("http://proxy:8080"));
+    let all = NetworkSettings { no_proxy: Some("*".into()), ..network() };
+    assert_eq!(all.proxy_for("https://registry.npmjs.org/vue"), None);
+    let https = NetworkSettings { https_proxy: Some("http://secure:3128".into()), no_proxy: None, ..network() };
+    assert_eq!(https.proxy_for("https://registry.npmjs.org/vue"), Some("http://secure:3128"));
+    assert_eq!(https.proxy_for("http://registry.npmjs.org/vue"), Some("http://proxy:8080"));
+  }
+}
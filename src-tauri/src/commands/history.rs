@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::ipc::Channel;
+use tauri::Manager;
+
+use super::job;
+use super::project::{self, CreateOptions};
+use super::reporter::{self, CreateEvent};
+
+// 串行化 history.json 的读改写，避免并发创建时互相覆盖
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryStatus {
+  Running,
+  Success,
+  Failed,
+}
+
+// 一次创建（新建 / 续跑 / 重跑）的记录，id 与任务 id 一致
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryRecord {
+  pub id: String,
+  pub kind: String,
+  pub options: CreateOptions,
+  pub started_at: u64,
+  #[serde(default)]
+  pub finished_at: Option<u64>,
+  pub status: HistoryStatus,
+  #[serde(default)]
+  pub error: Option<String>,
+  #[serde(default)]
+  pub log_path: Option<String>,
+  pub project_dir: String,
+}
+
+fn history_path(app_handle: &tauri::AppHandle) -> Option<std::path::PathBuf> {
+  app_handle.path().app_data_dir().ok().map(|mut p| { p.push("history.json"); p })
+}
+
+// 文件不存在时为空；读取或解析失败时报错，避免随后的写入覆盖原有记录
+fn load(app_handle: &tauri::AppHandle) -> Result<Vec<HistoryRecord>, String> {
+  let path = history_path(app_handle).ok_or_else(|| "data dir not available".to_string())?;
+  if !path.exists() { return Ok(Vec::new()); }
+  let content = std::fs::read_to_string(&path).map_err(|e| format!("读取 history.json 失败：{}", e))?;
+  serde_json::from_str(&content).map_err(|e| format!("history.json 格式错误（{}）：{}", path.display(), e))
+}
+
+fn update<T>(app_handle: &tauri::AppHandle, f: impl FnOnce(&mut Vec<HistoryRecord>) -> T) -> Result<T, String> {
+  let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  let path = history_path(app_handle).ok_or_else(|| "data dir not available".to_string())?;
+  let mut records = load(app_handle)?;
+  let out = f(&mut records);
+  if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+  let content = serde_json::to_string_pretty(&records).map_err(|e| e.to_string())?;
+  std::fs::write(&path, content).map_err(|e| e.to_string())?;
+  Ok(out)
+}
+
+pub fn start(app_handle: &tauri::AppHandle, id: &str, kind: &str, opts: &CreateOptions, log_path: Option<String>) -> Result<(), String> {
+  let record = HistoryRecord {
+    id: id.to_string(),
+    kind: kind.to_string(),
+    options: opts.clone(),
+    started_at: job::now_millis(),
+    finished_at: None,
+    status: HistoryStatus::Running,
+    error: None,
+    log_path,
+    project_dir: opts.project_dir().to_string_lossy().to_string(),
+  };
+  update(app_handle, |records| records.push(record))
+}
+
+pub fn finish(app_handle: &tauri::AppHandle, id: &str, result: &Result<(), String>) -> Result<(), String> {
+  update(app_handle, |records| {
+    if let Some(r) = records.iter_mut().find(|r| r.id == id) {
+      r.finished_at = Some(job::now_millis());
+      r.status = if result.is_ok() { HistoryStatus::Success } else { HistoryStatus::Failed };
+      r.error = result.as_ref().err().cloned();
+    }
+  })
+}
+
+// 日志文件已被清理的记录不再指向它
+pub fn clear_log_paths(app_handle: &tauri::AppHandle, ids: &[String]) -> Result<(), String> {
+  update(app_handle, |records| {
+    for r in records.iter_mut().filter(|r| ids.contains(&r.id)) { r.log_path = None; }
+  })
+}
+
+// 按时间倒序返回全部记录
+#[tauri::command]
+pub fn list_history(app_handle: tauri::AppHandle) -> Result<Vec<HistoryRecord>, String> {
+  let mut records = load(&app_handle)?;
+  records.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+  Ok(records)
+}
+
+#[tauri::command]
+pub fn get_history_record(app_handle: tauri::AppHandle, id: String) -> Result<HistoryRecord, String> {
+  load(&app_handle)?
+    .into_iter()
+    .find(|r| r.id == id)
+    .ok_or_else(|| format!("未找到历史记录：{}", id))
+}
+
+// 删除指定记录，返回删除条数
+#[tauri::command]
+pub fn delete_history_records(app_handle: tauri::AppHandle, ids: Vec<String>) -> Result<usize, String> {
+  update(&app_handle, |records| {
+    let before = records.len();
+    records.retain(|r| !ids.contains(&r.id));
+    before - records.len()
+  })
+}
+
+// 以相同参数重跑历史记录到新的项目名（可选新目录），返回新任务 id
+#[tauri::command]
+pub async fn rerun_history(
+  app_handle: tauri::AppHandle,
+  id: String,
+  name: String,
+  directory: Option<String>,
+  on_event: Option<Channel<CreateEvent>>,
+  broadcast: Option<bool>,
+) -> Result<String, String> {
+  let record = get_history_record(app_handle.clone(), id)?;
+  if name.trim().is_empty() { return Err("项目名称不能为空".into()); }
+  let mut opts = record.options;
+  opts.name = name;
+  if let Some(dir) = directory.filter(|d| !d.trim().is_empty()) { opts.directory = dir; }
+  project::resolve_features(&app_handle, &mut opts)?;
+  project::check_target_dir(opts.directory.clone(), opts.name.clone())?;
+  if !Path::new(&opts.directory).exists() { std::fs::create_dir_all(&opts.directory).map_err(|e| e.to_string())?; }
+  let broadcast = reporter::broadcast_default(&on_event, broadcast);
+  Ok(project::spawn_create(app_handle, opts, false, "rerun", on_event, broadcast))
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::project::CreateOptions;
+
+// 任务状态文件：写在项目目录下，记录已完成的步骤，失败后可从失败步骤续跑
+pub const JOB_FILE: &str = ".create-job.json";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JobState {
+  pub options: CreateOptions,
+  #[serde(default)]
+  pub completed: Vec<String>,
+  #[serde(default)]
+  pub failed_step: Option<String>,
+  #[serde(default)]
+  pub error: Option<String>,
+  #[serde(default)]
+  pub updated_at: u64,
+}
+
+pub fn now_millis() -> u64 {
+  std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .map(|d| d.as_millis() as u64)
+    .unwrap_or(0)
+}
+
+// 任务 id：毫秒时间戳 + 进程内序号，同时用作历史记录与日志的标识
+pub fn new_id() -> String {
+  static SEQ: AtomicU64 = AtomicU64::new(0);
+  format!("{}-{}", now_millis(), SEQ.fetch_add(1, Ordering::Relaxed))
+}
+
+// 任务线程 panic 时的错误信息，任务据此以失败结束
+pub fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+  let detail = payload.downcast_ref::<&str>().map(|s| s.to_string())
+    .or_else(|| payload.downcast_ref::<String>().cloned())
+    .unwrap_or_default();
+  format!("任务异常终止：{}", detail)
+}
+
+fn job_file(project_dir: &Path) -> PathBuf {
+  project_dir.join(JOB_FILE)
+}
+
+pub fn exists(project_dir: &Path) -> bool {
+  job_file(project_dir).is_file()
+}
+
+// 清空项目目录（保留任务状态文件），用于续跑或重试时覆盖生成脚手架
+pub fn clear_except_state(project_dir: &Path) -> Result<(), String> {
+  if !project_dir.exists() { return Ok(()); }
+  for entry in std::fs::read_dir(project_dir).map_err(|e| e.to_string())? {
+    let path = entry.map_err(|e| e.to_string())?.path();
+    if path.file_name().and_then(|n| n.to_str()) == Some(JOB_FILE) { continue; }
+    let res = if path.is_dir() { std::fs::remove_dir_all(&path) } else { std::fs::remove_file(&path) };
+    res.map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
+
+impl JobState {
+  pub fn new(options: CreateOptions) -> Self {
+    JobState { options, completed: Vec::new(), failed_step: None, error: None, updated_at: now_millis() }
+  }
+
+  // 读取项目目录中的任务状态；不存在时返回 None
+  pub fn load(project_dir: &Path) -> Result<Option<Self>, String> {
+    let path = job_file(project_dir);
+    if !path.exists() { return Ok(None); }
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let state: JobState = serde_json::from_str(&content).map_err(|e| format!("任务状态文件损坏：{}", e))?;
+    Ok(Some(state))
+  }
+
+  pub fn save(&mut self, project_dir: &Path) -> Result<(), String> {
+    self.updated_at = now_millis();
+    std::fs::create_dir_all(project_dir).map_err(|e| e.to_string())?;
+    let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+    std::fs::write(job_file(project_dir), content).map_err(|e| e.to_string())
+  }
+
+  // 全部步骤完成后清理状态文件，避免残留在生成的项目中
+  pub fn remove(project_dir: &Path) {
+    let _ = std::fs::remove_file(job_file(project_dir));
+  }
+
+  pub fn is_completed(&self, step: &str) -> bool {
+    self.completed.iter().any(|s| s == step)
+  }
+
+  pub fn mark_completed(&mut self, step: &str) {
+    if !self.is_completed(step) { self.completed.push(step.to_string()); }
+    if self.failed_step.as_deref() == Some(step) {
+      self.failed_step = None;
+      self.error = None;
+    }
+  }
+
+  pub fn mark_failed(&mut self, step: &str, error: &str) {
+    self.failed_step = Some(step.to_string());
+    self.error = Some(error.to_string());
+  }
+}
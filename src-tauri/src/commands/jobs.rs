@@ -0,0 +1,242 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tauri::ipc::Channel;
+
+use super::job;
+use super::logs::LogLine;
+use super::project::CreateOptions;
+use super::reporter::{self, CreateEvent, StepProgress, StepStatus};
+
+// 内存中的任务登记表：记录当前步骤、进度与缓冲的日志行，前端刷新后可据此重新接入
+static JOBS: Mutex<Vec<JobEntry>> = Mutex::new(Vec::new());
+
+// 每个任务缓冲的日志行上限，超出时丢弃最早的行（完整日志见磁盘日志文件）
+const MAX_LINES: usize = 5000;
+// 保留的已结束任务数量上限
+const MAX_FINISHED: usize = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+  Queued,
+  Running,
+  Success,
+  Failed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct JobInfo {
+  pub id: String,
+  pub kind: String,
+  pub name: String,
+  pub project_dir: String,
+  pub status: JobStatus,
+  pub step: Option<String>,
+  pub completed_steps: usize,
+  pub total_steps: usize,
+  pub steps: Vec<StepProgress>,
+  pub queued_at: u64,
+  pub started_at: Option<u64>,
+  // 排队中的任务在队列中的位置（从 0 开始），按此顺序启动
+  pub queue_position: Option<usize>,
+  pub finished_at: Option<u64>,
+  pub error: Option<String>,
+  // 已产生的日志行总数（含已丢弃的行），即下一行的序号
+  pub line_count: usize,
+}
+
+struct JobEntry {
+  info: JobInfo,
+  lines: VecDeque<LogLine>,
+  // 因超出上限被丢弃的行数，缓冲中第 i 行的序号为 dropped + i
+  dropped: usize,
+  // 通过 IPC 通道订阅该任务的调用方；任务结束后释放
+  subscribers: Vec<Channel<CreateEvent>>,
+  // 发送顺序锁：在登记表锁内获取、释放登记表锁后再发送，同一任务的消息按产生顺序送达
+  gate: Arc<Mutex<()>>,
+}
+
+// 重新接入时的回放：first_seq 为 lines 第一行的序号，之后的行经事件或通道推送（带 seq）
+#[derive(Clone, Debug, Serialize)]
+pub struct JobReplay {
+  pub job: JobInfo,
+  pub first_seq: usize,
+  pub lines: Vec<LogLine>,
+}
+
+fn with_job<T>(id: &str, f: impl FnOnce(&mut JobEntry) -> T) -> Option<T> {
+  let mut jobs = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+  jobs.iter_mut().find(|j| j.info.id == id).map(f)
+}
+
+// 在登记表锁内更新任务，并取得要发送的消息与接收的通道（默认为全部订阅者）；
+// 发送在释放登记表锁之后进行，IPC 较慢或窗口卡住时不阻塞其他任务
+fn with_job_send<T>(id: &str, f: impl FnOnce(&mut JobEntry) -> (T, Vec<CreateEvent>, Option<Vec<Channel<CreateEvent>>>)) -> Option<T> {
+  let mut jobs = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+  let j = jobs.iter_mut().find(|j| j.info.id == id)?;
+  let (out, events, targets) = f(j);
+  let targets = targets.unwrap_or_else(|| j.subscribers.clone());
+  let gate = j.gate.clone();
+  let order = gate.lock().unwrap_or_else(|e| e.into_inner());
+  drop(jobs);
+  let mut failed: Vec<u32> = Vec::new();
+  for c in &targets {
+    if events.iter().any(|e| c.send(e.clone()).is_err()) { failed.push(c.id()); }
+  }
+  // 先释放发送顺序锁再取登记表锁（加锁顺序始终是登记表锁在前），丢弃已失效的通道（如窗口已刷新）
+  drop(order);
+  if !failed.is_empty() { with_job(id, |j| j.subscribers.retain(|c| !failed.contains(&c.id()))); }
+  Some(out)
+}
+
+pub fn register(id: &str, kind: &str, opts: &CreateOptions) {
+  let mut jobs = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+  push_entry(&mut jobs, id, kind, opts);
+}
+
+// 同 register，但同一项目目录已有排队或运行中的任务时拒绝（检查与登记在同一次加锁内完成）
+pub fn register_exclusive(id: &str, kind: &str, opts: &CreateOptions) -> Result<(), String> {
+  let mut jobs = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+  let project_dir = opts.project_dir().to_string_lossy().to_string();
+  if let Some(j) = jobs.iter().find(|j| !j.is_finished() && j.info.project_dir == project_dir) {
+    return Err(format!("该项目已有未结束的创建任务（{}），请等待其结束后再续跑", j.info.id));
+  }
+  push_entry(&mut jobs, id, kind, opts);
+  Ok(())
+}
+
+fn push_entry(jobs: &mut Vec<JobEntry>, id: &str, kind: &str, opts: &CreateOptions) {
+  let info = JobInfo {
+    id: id.to_string(),
+    kind: kind.to_string(),
+    name: opts.name.clone(),
+    project_dir: opts.project_dir().to_string_lossy().to_string(),
+    status: JobStatus::Queued,
+    step: None,
+    completed_steps: 0,
+    total_steps: 0,
+    steps: Vec::new(),
+    queued_at: job::now_millis(),
+    started_at: None,
+    queue_position: None,
+    finished_at: None,
+    error: None,
+    line_count: 0,
+  };
+  // 清理过多的已结束任务（最早的优先）
+  let finished = jobs.iter().filter(|j| j.is_finished()).count();
+  if finished >= MAX_FINISHED {
+    if let Some(pos) = jobs.iter().position(|j| j.is_finished()) { jobs.remove(pos); }
+  }
+  jobs.push(JobEntry { info, lines: VecDeque::new(), dropped: 0, subscribers: Vec::new(), gate: Arc::default() });
+}
+
+impl JobEntry {
+  fn is_finished(&self) -> bool {
+    matches!(self.info.status, JobStatus::Success | JobStatus::Failed)
+  }
+
+  fn done_event(&self) -> Option<CreateEvent> {
+    match self.info.status {
+      JobStatus::Queued | JobStatus::Running => None,
+      status => Some(CreateEvent::Done { success: status == JobStatus::Success, error: self.info.error.clone() }),
+    }
+  }
+}
+
+pub fn subscribe(id: &str, channel: Channel<CreateEvent>) {
+  with_job(id, |j| j.subscribers.push(channel));
+}
+
+pub fn publish(id: &str, event: CreateEvent) {
+  with_job_send(id, |_| ((), vec![event], None));
+}
+
+// 任务是否仍在排队或运行
+pub fn is_active(id: &str) -> bool {
+  with_job(id, |j| !j.is_finished()).unwrap_or(false)
+}
+
+// 任务出队开始执行
+pub fn set_running(id: &str) {
+  with_job(id, |j| { j.info.status = JobStatus::Running; j.info.started_at = Some(job::now_millis()); });
+}
+
+pub fn set_step(id: &str, step: Option<&str>) {
+  with_job(id, |j| j.info.step = step.map(|s| s.to_string()));
+}
+
+pub fn set_steps(id: &str, steps: &[StepProgress]) {
+  with_job(id, |j| {
+    j.info.completed_steps = steps.iter().filter(|s| matches!(s.status, StepStatus::Done | StepStatus::Skipped)).count();
+    j.info.total_steps = steps.len();
+    j.info.steps = steps.to_vec();
+  });
+}
+
+// 追加日志行并发给订阅者，返回该行序号；任务未登记时返回 None
+pub fn push_line(id: &str, line: &LogLine) -> Option<usize> {
+  with_job_send(id, |j| {
+    let seq = j.info.line_count;
+    let event = CreateEvent::Log { seq, step: line.step.clone(), stream: line.stream, line: line.line.clone() };
+    j.lines.push_back(line.clone());
+    if j.lines.len() > MAX_LINES {
+      j.lines.pop_front();
+      j.dropped += 1;
+    }
+    j.info.line_count += 1;
+    (seq, vec![event], None)
+  })
+}
+
+pub fn finish(id: &str, result: &Result<(), String>) {
+  with_job_send(id, |j| {
+    j.info.status = if result.is_ok() { JobStatus::Success } else { JobStatus::Failed };
+    j.info.error = result.as_ref().err().cloned();
+    j.info.step = None;
+    j.info.finished_at = Some(job::now_millis());
+    ((), j.done_event().into_iter().collect(), Some(std::mem::take(&mut j.subscribers)))
+  });
+}
+
+// 列出登记表中的任务（按提交时间倒序）；登记顺序即入队顺序，据此给出排队位置
+#[tauri::command]
+pub fn list_jobs() -> Result<Vec<JobInfo>, String> {
+  let jobs = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+  let mut position = 0;
+  let mut out: Vec<JobInfo> = jobs
+    .iter()
+    .map(|j| {
+      let mut info = j.info.clone();
+      if info.status == JobStatus::Queued { info.queue_position = Some(position); position += 1; }
+      info
+    })
+    .collect();
+  out.sort_by_key(|j| std::cmp::Reverse(j.queued_at));
+  Ok(out)
+}
+
+// 重新接入任务：返回任务状态与从头开始的缓冲日志
+// 提供 on_event 时，缓冲日志经通道回放（返回值中 lines 为空），之后的消息持续推送到该通道，不会遗漏或重复；
+// 否则前端应先监听全局事件再调用，并丢弃 seq 小于回放末尾的事件
+#[tauri::command]
+pub fn subscribe_job(id: String, on_event: Option<Channel<CreateEvent>>) -> Result<JobReplay, String> {
+  with_job_send(&id, |j| {
+    let first_seq = j.dropped;
+    let Some(channel) = on_event else {
+      return (JobReplay { job: j.info.clone(), first_seq, lines: j.lines.iter().cloned().collect() }, Vec::new(), Some(Vec::new()));
+    };
+    // 回放只发给新通道；之后的消息须等回放发送完才能取得发送顺序锁
+    let mut events: Vec<CreateEvent> = j.lines.iter().enumerate()
+      .map(|(i, l)| CreateEvent::Log { seq: first_seq + i, step: l.step.clone(), stream: l.stream, line: l.line.clone() })
+      .collect();
+    if !j.info.steps.is_empty() { events.push(reporter::progress_event(&j.info.steps)); }
+    match j.done_event() {
+      Some(event) => events.push(event),
+      None => j.subscribers.push(channel.clone()),
+    }
+    (JobReplay { job: j.info.clone(), first_seq, lines: Vec::new() }, events, Some(vec![channel]))
+  })
+  .ok_or_else(|| format!("任务不存在或已被清理：{}", id))
+}
@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use super::config;
+use super::history;
+use super::job;
+use super::jobs;
+
+// 日志行来源：流程提示 / 子进程标准输出 / 子进程标准错误
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+  Info,
+  Stdout,
+  Stderr,
+}
+
+impl Stream {
+  fn as_str(&self) -> &'static str {
+    match self { Stream::Info => "info", Stream::Stdout => "stdout", Stream::Stderr => "stderr" }
+  }
+}
+
+// 日志文件中的一行（JSON Lines 格式），ts 为毫秒时间戳
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogLine {
+  pub ts: u64,
+  #[serde(default)]
+  pub step: Option<String>,
+  pub stream: Stream,
+  pub line: String,
+}
+
+fn format_ts(ts: u64) -> String {
+  chrono::DateTime::from_timestamp_millis(ts as i64)
+    .map(|d| d.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+    .unwrap_or_else(|| ts.to_string())
+}
+
+impl LogLine {
+  fn to_text(&self) -> String {
+    format!("{} [{}] [{}] {}", format_ts(self.ts), self.step.as_deref().unwrap_or("-"), self.stream.as_str(), self.line)
+  }
+}
+
+fn logs_dir(app_handle: &tauri::AppHandle) -> Option<PathBuf> {
+  app_handle.path().app_log_dir().ok().map(|p| p.join("jobs"))
+}
+
+// 任务 id 仅由数字、字母与连字符组成，拒绝其他字符以免拼出目录外的路径
+fn log_path(app_handle: &tauri::AppHandle, id: &str) -> Result<PathBuf, String> {
+  if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+    return Err(format!("无效的日志 id：{}", id));
+  }
+  let dir = logs_dir(app_handle).ok_or_else(|| "log dir not available".to_string())?;
+  Ok(dir.join(format!("{}.jsonl", id)))
+}
+
+// 追加写入的任务日志文件
+pub struct LogWriter {
+  file: std::fs::File,
+  path: PathBuf,
+}
+
+impl LogWriter {
+  pub fn create(app_handle: &tauri::AppHandle, id: &str) -> Result<Self, String> {
+    let path = log_path(app_handle, id)?;
+    if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+    let file = std::fs::OpenOptions::new().create(true).append(true).open(&path).map_err(|e| e.to_string())?;
+    Ok(LogWriter { file, path })
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  pub fn write(&mut self, line: &LogLine) {
+    if let Ok(s) = serde_json::to_string(line) { let _ = writeln!(self.file, "{}", s); }
+  }
+}
+
+fn read_lines(path: &Path) -> Result<Vec<LogLine>, String> {
+  let file = std::fs::File::open(path).map_err(|e| format!("读取日志失败：{}", e))?;
+  Ok(BufReader::new(file)
+    .lines()
+    .map_while(Result::ok)
+    .filter_map(|l| serde_json::from_str::<LogLine>(&l).ok())
+    .collect())
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LogInfo {
+  pub id: String,
+  pub path: String,
+  pub size_bytes: u64,
+  pub modified_at: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LogPage {
+  pub lines: Vec<LogLine>,
+  pub offset: usize,
+  pub total: usize,
+  pub has_more: bool,
+}
+
+fn modified_millis(meta: &std::fs::Metadata) -> u64 {
+  meta.modified()
+    .ok()
+    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+    .map(|d| d.as_millis() as u64)
+    .unwrap_or(0)
+}
+
+// 删除修改时间早于 max_age_days 天的日志，返回删除数量；0 表示永久保留。
+// 排队或运行中任务的日志不删除；删除后清除对应历史记录中的日志路径
+pub fn prune(app_handle: &tauri::AppHandle, max_age_days: u32) -> Result<usize, String> {
+  if max_age_days == 0 { return Ok(0); }
+  let dir = match logs_dir(app_handle) { Some(d) if d.exists() => d, _ => return Ok(0) };
+  let cutoff = job::now_millis().saturating_sub(max_age_days as u64 * 24 * 3600 * 1000);
+  let mut removed: Vec<String> = Vec::new();
+  for entry in std::fs::read_dir(&dir).map_err(|e| e.to_string())?.flatten() {
+    let meta = match entry.metadata() { Ok(m) if m.is_file() => m, _ => continue };
+    let path = entry.path();
+    let id = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    if modified_millis(&meta) >= cutoff || jobs::is_active(&id) { continue; }
+    if std::fs::remove_file(&path).is_ok() { removed.push(id); }
+  }
+  if !removed.is_empty() { history::clear_log_paths(app_handle, &removed)?; }
+  Ok(removed.len())
+}
+
+// 按修改时间倒序列出全部任务日志
+#[tauri::command]
+pub fn list_logs(app_handle: tauri::AppHandle) -> Result<Vec<LogInfo>, String> {
+  let dir = match logs_dir(&app_handle) { Some(d) if d.exists() => d, _ => return Ok(Vec::new()) };
+  let mut out: Vec<LogInfo> = std::fs::read_dir(&dir)
+    .map_err(|e| e.to_string())?
+    .flatten()
+    .filter_map(|entry| {
+      let path = entry.path();
+      if path.extension().and_then(|e| e.to_str()) != Some("jsonl") { return None; }
+      let meta = entry.metadata().ok()?;
+      Some(LogInfo {
+        id: path.file_stem()?.to_string_lossy().to_string(),
+        path: path.to_string_lossy().to_string(),
+        size_bytes: meta.len(),
+        modified_at: modified_millis(&meta),
+      })
+    })
+    .collect();
+  out.sort_by_key(|l| std::cmp::Reverse(l.modified_at));
+  Ok(out)
+}
+
+// 分页读取日志：offset 为起始行号，limit 缺省 500 行
+#[tauri::command]
+pub fn read_log(app_handle: tauri::AppHandle, id: String, offset: Option<usize>, limit: Option<usize>) -> Result<LogPage, String> {
+  let lines = read_lines(&log_path(&app_handle, &id)?)?;
+  let total = lines.len();
+  let offset = offset.unwrap_or(0).min(total);
+  let limit = limit.unwrap_or(500).max(1);
+  let page: Vec<LogLine> = lines.into_iter().skip(offset).take(limit).collect();
+  let has_more = offset + page.len() < total;
+  Ok(LogPage { lines: page, offset, total, has_more })
+}
+
+// 导出日志到用户选择的路径（纯文本，每行带时间、步骤与输出流标记）
+#[tauri::command]
+pub fn export_log(app_handle: tauri::AppHandle, id: String, path: String) -> Result<(), String> {
+  let lines = read_lines(&log_path(&app_handle, &id)?)?;
+  let mut content = String::new();
+  for l in lines { content.push_str(&l.to_text()); content.push('\n'); }
+  if let Some(dir) = Path::new(&path).parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+  std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+// 清理过期日志；max_age_days 缺省时使用设置中的保留天数，为 0 时不清理
+#[tauri::command]
+pub fn prune_logs(app_handle: tauri::AppHandle, max_age_days: Option<u32>) -> Result<usize, String> {
+  let days = max_age_days.unwrap_or_else(|| config::load_settings(&app_handle).log_retention_days);
+  prune(&app_handle, days)
+}
@@ -1,5 +1,23 @@
 pub mod env;
 pub mod config;
 pub mod project;
+pub mod job;
+pub mod jobs;
+pub mod queue;
+pub mod process;
+pub mod templates;
+pub mod store;
+pub mod scaffold_cache;
+pub mod history;
+pub mod batch;
+pub mod presets;
+pub mod logs;
+pub mod reporter;
+pub mod components;
 pub mod registry;
+pub mod registry_share;
+pub mod registry_sync;
+pub mod npm;
+pub mod npm_cache;
+pub mod compat;
 pub mod menu;
@@ -0,0 +1,459 @@
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::config::NetworkSettings;
+
+// npm registry 客户端：直接请求 registry 的 JSON 接口（GET <registry>/<包名>），无需安装 pnpm
+// registry 地址取自网络设置，可指向本地替身服务进行验证
+pub const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VersionMeta {
+  pub version: String,
+  // 发布时间（ISO 8601），来自包文档的 time 字段
+  #[serde(default)]
+  pub published_at: Option<String>,
+  // 弃用说明；未弃用为 None
+  #[serde(default)]
+  pub deprecated: Option<String>,
+  #[serde(default)]
+  pub peer_dependencies: BTreeMap<String, String>,
+  // peerDependenciesMeta 中标记为 optional 的包
+  #[serde(default)]
+  pub optional_peers: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PackageMeta {
+  pub name: String,
+  pub registry: String,
+  #[serde(default)]
+  pub description: Option<String>,
+  #[serde(default)]
+  pub keywords: Vec<String>,
+  #[serde(default)]
+  pub homepage: Option<String>,
+  pub dist_tags: BTreeMap<String, String>,
+  // 按发布时间从新到旧排列
+  pub versions: Vec<VersionMeta>,
+}
+
+#[derive(Deserialize)]
+struct RawPackage {
+  name: String,
+  #[serde(default)]
+  description: Option<String>,
+  // 个别包写成字符串，按非数组处理时忽略
+  #[serde(default)]
+  keywords: Value,
+  #[serde(default)]
+  homepage: Option<String>,
+  #[serde(default, rename = "dist-tags")]
+  dist_tags: BTreeMap<String, String>,
+  #[serde(default)]
+  versions: BTreeMap<String, RawVersion>,
+  // 除版本外还含 created / modified / unpublished 等键，值不一定是字符串
+  #[serde(default)]
+  time: BTreeMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct RawVersion {
+  #[serde(default, rename = "peerDependencies")]
+  peer_dependencies: BTreeMap<String, String>,
+  #[serde(default, rename = "peerDependenciesMeta")]
+  peer_dependencies_meta: BTreeMap<String, Value>,
+  // 通常为字符串；少数旧包为布尔值
+  #[serde(default)]
+  deprecated: Option<Value>,
+}
+
+impl RawPackage {
+  fn into_meta(self, registry: &str) -> PackageMeta {
+    let time = self.time;
+    let mut versions: Vec<VersionMeta> = self
+      .versions
+      .into_iter()
+      .map(|(version, raw)| {
+        let deprecated = match raw.deprecated {
+          Some(Value::String(s)) if !s.trim().is_empty() => Some(s),
+          Some(Value::Bool(true)) => Some(String::new()),
+          _ => None,
+        };
+        let optional_peers = raw
+          .peer_dependencies_meta
+          .iter()
+          .filter(|(_, m)| m.get("optional").and_then(|o| o.as_bool()).unwrap_or(false))
+          .map(|(k, _)| k.clone())
+          .collect();
+        VersionMeta {
+          published_at: time.get(&version).and_then(|t| t.as_str()).map(|s| s.to_string()),
+          version,
+          deprecated,
+          peer_dependencies: raw.peer_dependencies,
+          optional_peers,
+        }
+      })
+      .collect();
+    // ISO 时间可直接按字符串比较；缺少时间的排在最后
+    versions.sort_by(|a, b| b.published_at.cmp(&a.published_at));
+    PackageMeta {
+      name: self.name,
+      registry: registry.to_string(),
+      description: self.description,
+      keywords: string_list(&self.keywords),
+      homepage: self.homepage,
+      dist_tags: self.dist_tags,
+      versions,
+    }
+  }
+}
+
+fn string_list(v: &Value) -> Vec<String> {
+  v.as_array().map(|a| a.iter().filter_map(|x| x.as_str().map(|s| s.to_string())).collect()).unwrap_or_default()
+}
+
+// 包名在 URL 中的形式：作用域包的 "/" 需转义（@scope%2fname）
+fn package_path(package: &str) -> String {
+  package.replace('/', "%2f")
+}
+
+// 包实际使用的 registry 地址（未配置时使用官方 registry），不带末尾的 "/"
+pub fn registry_for(network: &NetworkSettings, package: &str) -> String {
+  network.registry_for(package).unwrap_or(DEFAULT_REGISTRY).trim_end_matches('/').to_string()
+}
+
+// 按网络设置构建 HTTP 客户端：代理、超时（None 表示不限制）
+pub fn agent(network: &NetworkSettings, url: &str, timeout: Option<Duration>) -> Result<ureq::Agent, String> {
+  let mut builder = ureq::AgentBuilder::new().user_agent(concat!("acps/", env!("CARGO_PKG_VERSION")));
+  if let Some(t) = timeout { builder = builder.timeout(t); }
+  if let Some(p) = network.proxy_for(url) {
+    builder = builder.proxy(ureq::Proxy::new(p).map_err(|e| format!("代理地址无效：{}", e))?);
+  }
+  Ok(builder.build())
+}
+
+// GET 请求 registry 的 JSON 接口；令牌仅发送给默认 registry
+pub fn get_json(network: &NetworkSettings, registry: &str, path: &str, query: &[(&str, &str)], timeout: Option<Duration>) -> Result<Value, String> {
+  let url = format!("{}/{}", registry, path);
+  let mut req = agent(network, &url, timeout)?.get(&url).set("Accept", "application/json");
+  for (k, v) in query { req = req.query(k, v); }
+  if let Some(token) = network.auth_token_for(registry) {
+    req = req.set("Authorization", &format!("Bearer {}", token));
+  }
+  match req.call() {
+    Ok(resp) => resp.into_json::<Value>().map_err(|e| format!("解析 registry 响应失败：{}", e)),
+    Err(ureq::Error::Status(404, _)) => Err(format!("registry 中不存在：{}", path)),
+    Err(ureq::Error::Status(code @ (401 | 403), _)) => Err(format!("registry 拒绝访问（{}），请检查访问令牌：{}", code, registry)),
+    Err(ureq::Error::Status(code, _)) => Err(format!("registry 返回错误（{}）：{}", code, url)),
+    Err(ureq::Error::Transport(t)) => Err(format!("访问 registry 失败（{}）：{}", registry, t)),
+  }
+}
+
+// 获取包的完整元数据：版本、dist-tags、发布时间、peerDependencies 与弃用信息
+pub fn fetch_package(network: &NetworkSettings, package: &str, timeout: Option<Duration>) -> Result<PackageMeta, String> {
+  let registry = registry_for(network, package);
+  let value = get_json(network, &registry, &package_path(package), &[], timeout)?;
+  let raw: RawPackage = serde_json::from_value(value).map_err(|e| format!("解析包信息失败：{}", e))?;
+  Ok(raw.into_meta(&registry))
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SearchResult {
+  pub name: String,
+  pub description: Option<String>,
+  // 最新版本
+  pub version: String,
+  pub keywords: Vec<String>,
+  pub homepage: Option<String>,
+  pub repository: Option<String>,
+  pub date: Option<String>,
+  pub score: f64,
+}
+
+// 调用 registry 的搜索接口（GET <registry>/-/v1/search?text=..&size=..）；使用默认 registry
+pub fn search(network: &NetworkSettings, text: &str, size: usize, timeout: Option<Duration>) -> Result<Vec<SearchResult>, String> {
+  let registry = network.registry_url().unwrap_or(DEFAULT_REGISTRY).trim_end_matches('/').to_string();
+  let size = size.to_string();
+  let value = get_json(network, &registry, "-/v1/search", &[("text", text), ("size", &size)], timeout)?;
+  let objects = value.get("objects").and_then(|o| o.as_array()).ok_or_else(|| "registry 搜索响应格式错误".to_string())?;
+  let text_of = |v: &Value, key: &str| v.get(key).and_then(|x| x.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string());
+  Ok(objects
+    .iter()
+    .filter_map(|o| {
+      let p = o.get("package")?;
+      let links = p.get("links").cloned().unwrap_or(Value::Null);
+      Some(SearchResult {
+        name: text_of(p, "name")?,
+        description: text_of(p, "description"),
+        version: text_of(p, "version").unwrap_or_default(),
+        keywords: p.get("keywords").map(string_list).unwrap_or_default(),
+        homepage: text_of(&links, "homepage"),
+        repository: text_of(&links, "repository"),
+        date: text_of(p, "date"),
+        score: o.get("score").and_then(|s| s.get("final")).and_then(|f| f.as_f64()).unwrap_or(0.0),
+      })
+    })
+    .collect())
+}
+
+// npm 范围中的单个比较符转为 semver 写法：裸版本号在 npm 中表示精确匹配（1.2 表示 1.2.x），需补 "="
+fn convert_comparator(token: &str) -> String {
+  let op_len = token.find(|c: char| !matches!(c, '<' | '>' | '=' | '^' | '~')).unwrap_or(token.len());
+  let (op, ver) = token.split_at(op_len);
+  let ver = ver.trim_start_matches(['v', 'V']);
+  let op = if op.is_empty() && !ver.contains(['x', 'X', '*']) { "=" } else { op };
+  format!("{}{}", op, ver)
+}
+
+// 解析 npm 版本范围（如 "^2.6.0 || ^3.0.0"、">= 2.7 < 3"、"1.2.3 - 1.4"）：
+// "||" 分隔的每组内比较符同时满足，任一组满足即可
+pub fn parse_range(range: &str) -> Result<Vec<VersionReq>, String> {
+  range
+    .split("||")
+    .map(|set| {
+      let set = set.trim();
+      let converted = if let Some((from, to)) = set.split_once(" - ") {
+        format!(">={}, <={}", from.trim().trim_start_matches(['v', 'V']), to.trim().trim_start_matches(['v', 'V']))
+      } else {
+        // 合并与版本号之间有空格的比较符（如 ">= 1.2"）
+        let mut tokens: Vec<String> = Vec::new();
+        let mut pending = String::new();
+        for t in set.split_whitespace() {
+          if t.chars().all(|c| matches!(c, '<' | '>' | '=' | '^' | '~')) { pending.push_str(t); continue; }
+          tokens.push(convert_comparator(&format!("{}{}", std::mem::take(&mut pending), t)));
+        }
+        if tokens.is_empty() { "*".to_string() } else { tokens.join(", ") }
+      };
+      VersionReq::parse(&converted).map_err(|e| format!("无法解析版本范围 {}：{}", range, e))
+    })
+    .collect()
+}
+
+pub fn satisfies(reqs: &[VersionReq], version: &Version) -> bool {
+  reqs.iter().any(|r| r.matches(version))
+}
+
+// 版本列表的过滤条件；range 可为 npm 范围或 dist-tag 名称（如 "latest"、"legacy"）
+#[derive(Clone, Debug, Default)]
+pub struct VersionFilter {
+  pub include_prerelease: bool,
+  pub include_deprecated: bool,
+  pub range: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VersionEntry {
+  pub version: String,
+  pub published_at: Option<String>,
+  pub deprecated: Option<String>,
+  pub prerelease: bool,
+  // 指向该版本的 dist-tags
+  pub tags: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MajorGroup {
+  pub major: u64,
+  // 该主版本下最新的正式版（非预发布、未弃用）
+  pub latest: Option<String>,
+  pub versions: Vec<VersionEntry>,
+}
+
+impl PackageMeta {
+  // 按 semver 从高到低列出满足条件的版本；无法解析为 semver 的版本忽略
+  pub fn list_versions(&self, filter: &VersionFilter) -> Result<Vec<VersionEntry>, String> {
+    let range = filter.range.as_deref().map(|r| r.trim()).filter(|r| !r.is_empty());
+    let tagged = range.and_then(|r| self.dist_tags.get(r));
+    let reqs = match (range, tagged) {
+      (Some(r), None) => Some(parse_range(r)?),
+      _ => None,
+    };
+    let mut list: Vec<(Version, VersionEntry)> = self
+      .versions
+      .iter()
+      .filter_map(|m| Version::parse(m.version.trim_start_matches(['v', 'V'])).ok().map(|v| (v, m)))
+      .filter(|(v, m)| match (tagged, &reqs) {
+        (Some(t), _) => &m.version == t,
+        (None, Some(reqs)) => satisfies(reqs, v),
+        (None, None) => true,
+      })
+      .filter(|(v, m)| tagged.is_some() || ((filter.include_prerelease || v.pre.is_empty()) && (filter.include_deprecated || m.deprecated.is_none())))
+      .map(|(v, m)| {
+        let tags = self.dist_tags.iter().filter(|(_, ver)| **ver == m.version).map(|(t, _)| t.clone()).collect();
+        let entry = VersionEntry {
+          version: m.version.clone(),
+          published_at: m.published_at.clone(),
+          deprecated: m.deprecated.clone(),
+          prerelease: !v.pre.is_empty(),
+          tags,
+        };
+        (v, entry)
+      })
+      .collect();
+    list.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok(list.into_iter().map(|(_, e)| e).collect())
+  }
+}
+
+// 按主版本分组（新的主版本在前），保留旧主版本线（如兼容 Vue 2 的版本）
+pub fn group_by_major(entries: Vec<VersionEntry>) -> Vec<MajorGroup> {
+  let mut groups: Vec<MajorGroup> = Vec::new();
+  for e in entries {
+    let major = Version::parse(e.version.trim_start_matches(['v', 'V'])).map(|v| v.major).unwrap_or(0);
+    if groups.last().map(|g| g.major) != Some(major) {
+      groups.push(MajorGroup { major, latest: None, versions: Vec::new() });
+    }
+    let Some(group) = groups.last_mut() else { continue };
+    if group.latest.is_none() && !e.prerelease && e.deprecated.is_none() { group.latest = Some(e.version.clone()); }
+    group.versions.push(e);
+  }
+  groups
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::{BufRead, BufReader, Write};
+  use std::net::TcpListener;
+  use std::sync::{Arc, Mutex};
+
+  // 收到的请求：(请求路径, 是否带 Authorization)
+  type Requests = Arc<Mutex<Vec<(String, bool)>>>;
+
+  const PACKUMENT: &str = r#"{
+    "name": "demo",
+    "description": "demo package",
+    "keywords": ["vue", "ui"],
+    "dist-tags": { "latest": "2.1.0", "legacy": "1.4.0", "next": "3.0.0-beta.1" },
+    "versions": {
+      "1.4.0": { "peerDependencies": { "vue": "^2.6.0" } },
+      "2.0.0": { "deprecated": "use 2.1.0" },
+      "2.1.0": { "peerDependencies": { "vue": "^3.0.0" }, "peerDependenciesMeta": { "vue": { "optional": true } } },
+      "3.0.0-beta.1": {}
+    },
+    "time": {
+      "created": "2020-01-01T00:00:00.000Z",
+      "modified": "2023-01-01T00:00:00.000Z",
+      "1.4.0": "2020-06-01T00:00:00.000Z",
+      "2.0.0": "2021-06-01T00:00:00.000Z",
+      "2.1.0": "2022-06-01T00:00:00.000Z",
+      "3.0.0-beta.1": "2023-01-01T00:00:00.000Z",
+      "unpublished": { "time": "2023-02-01T00:00:00.000Z" }
+    }
+  }"#;
+
+  // 本地 registry 替身：按路径返回包文档或错误状态，每个连接只处理一个请求
+  fn serve() -> (String, Requests) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let requests: Requests = Arc::default();
+    let seen = requests.clone();
+    std::thread::spawn(move || {
+      for stream in listener.incoming() {
+        let Ok(mut stream) = stream else { continue };
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let path = line.split_whitespace().nth(1).unwrap_or("").to_string();
+        let mut auth = false;
+        loop {
+          let mut header = String::new();
+          if reader.read_line(&mut header).unwrap_or(0) == 0 || header.trim().is_empty() { break; }
+          if header.to_ascii_lowercase().starts_with("authorization:") { auth = true; }
+        }
+        seen.lock().unwrap().push((path.clone(), auth));
+        let (status, body) = if path.ends_with("/missing") {
+          ("404 Not Found", "{}")
+        } else if path.ends_with("/private") {
+          ("401 Unauthorized", "{}")
+        } else {
+          ("200 OK", PACKUMENT)
+        };
+        let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body);
+      }
+    });
+    (base, requests)
+  }
+
+  fn network(registry: &str) -> NetworkSettings {
+    NetworkSettings { registry: Some(registry.to_string()), auth_token: Some("secret".into()), ..Default::default() }
+  }
+
+  #[test]
+  fn fetch_package_reads_packument() {
+    let (base, _) = serve();
+    let meta = fetch_package(&network(&base), "demo", None).unwrap();
+    assert_eq!(meta.registry, base);
+    assert_eq!(meta.keywords, vec!["vue", "ui"]);
+    assert_eq!(meta.dist_tags.get("latest").map(String::as_str), Some("2.1.0"));
+    assert_eq!(meta.dist_tags.get("legacy").map(String::as_str), Some("1.4.0"));
+    // 按发布时间从新到旧
+    let order: Vec<&str> = meta.versions.iter().map(|v| v.version.as_str()).collect();
+    assert_eq!(order, vec!["3.0.0-beta.1", "2.1.0", "2.0.0", "1.4.0"]);
+    let v210 = &meta.versions[1];
+    assert_eq!(v210.published_at.as_deref(), Some("2022-06-01T00:00:00.000Z"));
+    assert_eq!(v210.peer_dependencies.get("vue").map(String::as_str), Some("^3.0.0"));
+    assert_eq!(v210.optional_peers, vec!["vue"]);
+    assert_eq!(meta.versions[2].deprecated.as_deref(), Some("use 2.1.0"));
+    assert!(meta.versions[3].deprecated.is_none());
+  }
+
+  #[test]
+  fn list_versions_filters_by_tag_and_range() {
+    let (base, _) = serve();
+    let meta = fetch_package(&network(&base), "demo", None).unwrap();
+    let versions = |range: Option<&str>| -> Vec<String> {
+      let filter = VersionFilter { range: range.map(|r| r.to_string()), ..Default::default() };
+      meta.list_versions(&filter).unwrap().into_iter().map(|e| e.version).collect()
+    };
+    // 默认排除预发布与弃用版本
+    assert_eq!(versions(None), vec!["2.1.0", "1.4.0"]);
+    assert_eq!(versions(Some("legacy")), vec!["1.4.0"]);
+    assert_eq!(versions(Some("next")), vec!["3.0.0-beta.1"]);
+    assert_eq!(versions(Some("^1.0.0 || >= 2.1")), vec!["2.1.0", "1.4.0"]);
+    let groups = group_by_major(meta.list_versions(&VersionFilter { include_deprecated: true, include_prerelease: true, range: None }).unwrap());
+    let latest: Vec<(u64, Option<String>)> = groups.into_iter().map(|g| (g.major, g.latest)).collect();
+    assert_eq!(latest, vec![(3, None), (2, Some("2.1.0".into())), (1, Some("1.4.0".into()))]);
+  }
+
+  #[test]
+  fn scoped_package_uses_escaped_path_and_scope_registry() {
+    let (base, requests) = serve();
+    let mut net = network(&base);
+    let mirror = format!("{}/mirror", base);
+    net.scoped_registries.insert("corp".into(), mirror.clone());
+    let meta = fetch_package(&net, "@corp/ui", None).unwrap();
+    assert_eq!(meta.registry, mirror);
+    fetch_package(&net, "@other/ui", None).unwrap();
+    let seen = requests.lock().unwrap().clone();
+    // 令牌只发送给默认 registry，作用域镜像不带
+    assert_eq!(seen, vec![("/mirror/@corp%2fui".to_string(), false), ("/@other%2fui".to_string(), true)]);
+  }
+
+  #[test]
+  fn auth_header_only_sent_to_default_registry() {
+    let (base, requests) = serve();
+    let mut net = network(&base);
+    get_json(&net, &base, "demo", &[], None).unwrap();
+    get_json(&net, &format!("{}/", base), "demo", &[], None).unwrap();
+    get_json(&net, &format!("{}/other", base), "demo", &[], None).unwrap();
+    net.auth_token = None;
+    get_json(&net, &base, "demo", &[], None).unwrap();
+    let auth: Vec<bool> = requests.lock().unwrap().iter().map(|(_, a)| *a).collect();
+    assert_eq!(auth, vec![true, true, false, false]);
+  }
+
+  #[test]
+  fn maps_error_statuses() {
+    let (base, _) = serve();
+    let net = network(&base);
+    let missing = fetch_package(&net, "missing", None).unwrap_err();
+    assert!(missing.contains("registry 中不存在"), "{}", missing);
+    let denied = fetch_package(&net, "private", None).unwrap_err();
+    assert!(denied.contains("拒绝访问（401）"), "{}", denied);
+  }
+}
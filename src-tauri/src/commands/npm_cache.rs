@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::Manager;
+
+use super::job;
+use super::npm::PackageMeta;
+
+// npm 包元数据的磁盘缓存（应用缓存目录/npm-cache/<包名>.json），过期后仍保留，供网络失败时兜底
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CachedPackage {
+  pub package: String,
+  // 查询时使用的 registry；切换镜像后旧缓存不再命中
+  pub registry: String,
+  pub fetched_at: u64,
+  pub meta: PackageMeta,
+}
+
+impl CachedPackage {
+  pub fn is_fresh(&self, ttl_secs: u64) -> bool {
+    ttl_secs > 0 && job::now_millis().saturating_sub(self.fetched_at) < ttl_secs * 1000
+  }
+}
+
+fn cache_dir(app_handle: &tauri::AppHandle) -> Option<PathBuf> {
+  app_handle.path().app_cache_dir().ok().map(|p| p.join("npm-cache"))
+}
+
+// 作用域包名含 "/"，替换后作为文件名
+fn entry_path(app_handle: &tauri::AppHandle, package: &str) -> Option<PathBuf> {
+  cache_dir(app_handle).map(|d| d.join(format!("{}.json", package.replace('/', "+"))))
+}
+
+// 旧格式或其他 registry 的缓存视为未命中
+pub fn read(app_handle: &tauri::AppHandle, package: &str, registry: &str) -> Option<CachedPackage> {
+  let content = std::fs::read_to_string(entry_path(app_handle, package)?).ok()?;
+  let entry: CachedPackage = serde_json::from_str(&content).ok()?;
+  if entry.registry != registry { return None; }
+  Some(entry)
+}
+
+pub fn write(app_handle: &tauri::AppHandle, entry: &CachedPackage) -> Result<(), String> {
+  let path = entry_path(app_handle, &entry.package).ok_or_else(|| "cache dir not available".to_string())?;
+  if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+  let content = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+  std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+// 清除缓存：指定包名时只删除该包，否则清空整个目录；返回删除的条目数
+#[tauri::command]
+pub fn clear_npm_cache(app_handle: tauri::AppHandle, package: Option<String>) -> Result<usize, String> {
+  if let Some(package) = package.filter(|p| !p.trim().is_empty()) {
+    let Some(path) = entry_path(&app_handle, package.trim()) else { return Ok(0) };
+    if !path.exists() { return Ok(0); }
+    std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+    return Ok(1);
+  }
+  let Some(dir) = cache_dir(&app_handle) else { return Ok(0) };
+  let count = std::fs::read_dir(&dir).map(|it| it.flatten().count()).unwrap_or(0);
+  if dir.exists() { std::fs::remove_dir_all(&dir).map_err(|e| e.to_string())?; }
+  Ok(count)
+}
@@ -0,0 +1,375 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::ipc::Channel;
+use tauri::Manager;
+
+use super::components::{self, ComponentRegistry};
+use super::job;
+use super::project::{self, CreateOptions};
+use super::registry_share::{self, MergeCounts, MergeStrategy};
+use super::reporter::{self, CreateEvent};
+
+// 串行化 presets.json 的读改写
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn default_package_manager() -> String {
+  "pnpm".into()
+}
+
+// 命名的创建预设：框架版本、语言、特性、包管理器、创建后钩子与默认存放目录
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Preset {
+  pub name: String,
+  pub version: String,
+  pub lang: String,
+  #[serde(default)]
+  pub features: Vec<String>,
+  #[serde(default = "default_package_manager")]
+  pub package_manager: String,
+  #[serde(default)]
+  pub hooks: Vec<String>,
+  #[serde(default)]
+  pub directory: Option<String>,
+  #[serde(default)]
+  pub created_at: u64,
+  #[serde(default)]
+  pub updated_at: u64,
+}
+
+impl Preset {
+  // 校验字段；目前创建流程仅支持 pnpm
+  pub fn validate(&self) -> Result<(), String> {
+    if self.name.trim().is_empty() { return Err("预设名称不能为空".into()); }
+    if self.version != "vue3" && self.version != "vue2" { return Err(format!("不支持的 Vue 版本：{}", self.version)); }
+    if self.lang != "ts" && self.lang != "js" { return Err(format!("不支持的语言：{}", self.lang)); }
+    if self.package_manager != "pnpm" { return Err(format!("暂不支持的包管理器：{}（目前仅支持 pnpm）", self.package_manager)); }
+    Ok(())
+  }
+
+  // 以预设生成创建参数；directory 为空时使用预设中的目录
+  pub fn to_options(&self, name: &str, directory: Option<String>) -> Result<CreateOptions, String> {
+    let directory = directory
+      .filter(|d| !d.trim().is_empty())
+      .or_else(|| self.directory.clone().filter(|d| !d.trim().is_empty()))
+      .ok_or_else(|| format!("预设 {} 未设置存放目录，请指定目录", self.name))?;
+    Ok(CreateOptions {
+      version: self.version.clone(),
+      lang: self.lang.clone(),
+      name: name.to_string(),
+      directory,
+      features: self.features.clone(),
+      hooks: self.hooks.clone(),
+      create_vue: false,
+    })
+  }
+}
+
+fn presets_path(app_handle: &tauri::AppHandle) -> Option<std::path::PathBuf> {
+  app_handle.path().app_config_dir().ok().map(|mut p| { p.push("presets.json"); p })
+}
+
+// 文件不存在时为空；读取或解析失败时报错，避免随后的保存或导入覆盖原有预设
+pub fn load(app_handle: &tauri::AppHandle) -> Result<Vec<Preset>, String> {
+  let path = presets_path(app_handle).ok_or_else(|| "config dir not available".to_string())?;
+  if !path.exists() { return Ok(Vec::new()); }
+  let content = std::fs::read_to_string(&path).map_err(|e| format!("读取 presets.json 失败：{}", e))?;
+  serde_json::from_str(&content).map_err(|e| format!("presets.json 格式错误（{}）：{}", path.display(), e))
+}
+
+pub fn find(app_handle: &tauri::AppHandle, name: &str) -> Result<Preset, String> {
+  load(app_handle)?.into_iter().find(|p| p.name == name).ok_or_else(|| format!("未找到预设：{}", name))
+}
+
+pub fn update<T>(app_handle: &tauri::AppHandle, f: impl FnOnce(&mut Vec<Preset>) -> Result<T, String>) -> Result<T, String> {
+  let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  let path = presets_path(app_handle).ok_or_else(|| "config dir not available".to_string())?;
+  let mut presets = load(app_handle)?;
+  let out = f(&mut presets)?;
+  if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+  let content = serde_json::to_string_pretty(&presets).map_err(|e| e.to_string())?;
+  std::fs::write(&path, content).map_err(|e| e.to_string())?;
+  Ok(out)
+}
+
+#[tauri::command]
+pub fn list_presets(app_handle: tauri::AppHandle) -> Result<Vec<Preset>, String> {
+  load(&app_handle)
+}
+
+// 保存预设：同名时覆盖（保留创建时间）
+#[tauri::command]
+pub fn save_preset(app_handle: tauri::AppHandle, preset: Preset) -> Result<Preset, String> {
+  let mut preset = preset;
+  preset.name = preset.name.trim().to_string();
+  preset.validate()?;
+  update(&app_handle, |presets| {
+    let now = job::now_millis();
+    preset.updated_at = now;
+    match presets.iter_mut().find(|p| p.name == preset.name) {
+      Some(existing) => {
+        preset.created_at = existing.created_at;
+        *existing = preset.clone();
+      }
+      None => {
+        preset.created_at = now;
+        presets.push(preset.clone());
+      }
+    }
+    Ok(preset)
+  })
+}
+
+#[tauri::command]
+pub fn delete_preset(app_handle: tauri::AppHandle, name: String) -> Result<(), String> {
+  update(&app_handle, |presets| {
+    let before = presets.len();
+    presets.retain(|p| p.name != name);
+    if presets.len() == before { return Err(format!("未找到预设：{}", name)); }
+    Ok(())
+  })
+}
+
+#[tauri::command]
+pub fn rename_preset(app_handle: tauri::AppHandle, name: String, new_name: String) -> Result<(), String> {
+  let new_name = new_name.trim().to_string();
+  if new_name.is_empty() { return Err("预设名称不能为空".into()); }
+  update(&app_handle, |presets| {
+    if new_name != name && presets.iter().any(|p| p.name == new_name) { return Err(format!("预设已存在：{}", new_name)); }
+    let preset = presets.iter_mut().find(|p| p.name == name).ok_or_else(|| format!("未找到预设：{}", name))?;
+    preset.name = new_name;
+    preset.updated_at = job::now_millis();
+    Ok(())
+  })
+}
+
+// 按预设创建项目：directory 缺省时使用预设中的目录，返回任务 id
+#[tauri::command]
+pub async fn create_from_preset(
+  app_handle: tauri::AppHandle,
+  preset: String,
+  name: String,
+  directory: Option<String>,
+  on_event: Option<Channel<CreateEvent>>,
+  broadcast: Option<bool>,
+) -> Result<String, String> {
+  if name.trim().is_empty() { return Err("项目名称不能为空".into()); }
+  let preset = find(&app_handle, &preset)?;
+  preset.validate()?;
+  let mut opts = preset.to_options(name.trim(), directory)?;
+  project::resolve_features(&app_handle, &mut opts)?;
+  project::check_target_dir(opts.directory.clone(), opts.name.clone())?;
+  let broadcast = reporter::broadcast_default(&on_event, broadcast);
+  Ok(project::spawn_create(app_handle, opts, false, "preset", on_event, broadcast))
+}
+
+// 预设分享文件的格式标识与版本
+pub const EXPORT_FORMAT: &str = "acps-presets";
+const EXPORT_VERSION: u32 = 1;
+
+// 导入时与本地同名项冲突的处理方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+  #[default]
+  Skip,
+  Overwrite,
+  // 以新名称导入（预设名追加 " (2)"，组件 key 追加 "-2"，依次递增）
+  Rename,
+}
+
+// 分享文件：预设及其特性引用的 components.json 条目
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PresetBundle {
+  pub format: String,
+  pub version: u32,
+  #[serde(default)]
+  pub exported_at: u64,
+  pub presets: Vec<Preset>,
+  #[serde(default)]
+  pub components: Vec<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ExportSummary {
+  pub presets: usize,
+  pub components: usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ImportCounts {
+  pub added: usize,
+  pub overwritten: usize,
+  pub renamed: usize,
+  pub skipped: usize,
+  // 与本地内容完全一致，无需导入
+  pub unchanged: usize,
+}
+
+impl From<MergeCounts> for ImportCounts {
+  fn from(c: MergeCounts) -> Self {
+    ImportCounts { added: c.added, overwritten: c.updated, renamed: c.renamed, skipped: c.skipped, unchanged: c.unchanged }
+  }
+}
+
+// 分享文件中预设携带的钩子命令
+#[derive(Clone, Debug, Serialize)]
+pub struct IncomingHook {
+  pub preset: String,
+  pub command: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ImportSummary {
+  pub presets: ImportCounts,
+  pub components: ImportCounts,
+  // 组件改名导入时 key 的映射（旧 -> 新），对应预设的特性已同步改写
+  pub renamed_keys: std::collections::BTreeMap<String, String>,
+  // 文件中的全部钩子命令，供界面提示；hooks_imported 为 false 时均未导入
+  pub hooks: Vec<IncomingHook>,
+  pub hooks_imported: bool,
+}
+
+// 在已有名称中找一个不冲突的新名称
+pub fn unique_name(base: &str, taken: &dyn Fn(&str) -> bool, fmt: &dyn Fn(&str, usize) -> String) -> String {
+  let mut n = 2;
+  loop {
+    let candidate = fmt(base, n);
+    if !taken(&candidate) { return candidate; }
+    n += 1;
+  }
+}
+
+// 预设引用的组件条目，连同其 requires 的组件一并导出，保证导入后可通过校验
+fn bundle_components(registry: &ComponentRegistry, presets: &[Preset]) -> Vec<serde_json::Value> {
+  let keys: Vec<String> = presets.iter().flat_map(|p| p.features.iter().cloned()).collect();
+  let wanted = registry.with_requires(&keys);
+  registry
+    .components
+    .iter()
+    .filter(|c| wanted.contains(&c.key))
+    .filter_map(|c| serde_json::to_value(c).ok())
+    .collect()
+}
+
+// 导出预设（names 为空时导出全部）及其引用的组件条目
+#[tauri::command]
+pub fn export_presets(app_handle: tauri::AppHandle, names: Option<Vec<String>>, path: String) -> Result<ExportSummary, String> {
+  let presets: Vec<Preset> = load(&app_handle)?
+    .into_iter()
+    .filter(|p| names.as_ref().is_none_or(|n| n.contains(&p.name)))
+    .collect();
+  if presets.is_empty() { return Err("没有可导出的预设".into()); }
+  let components = bundle_components(&components::read(&app_handle)?, &presets);
+  let bundle = PresetBundle {
+    format: EXPORT_FORMAT.into(),
+    version: EXPORT_VERSION,
+    exported_at: job::now_millis(),
+    presets,
+    components,
+  };
+  let content = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+  if let Some(dir) = std::path::Path::new(&path).parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+  std::fs::write(&path, content).map_err(|e| e.to_string())?;
+  Ok(ExportSummary { presets: bundle.presets.len(), components: bundle.components.len() })
+}
+
+// 导入分享文件：先合并组件条目（改名时同步改写预设中的特性 key），再合并预设
+// 钩子会以 shell 执行，他人文件中的钩子默认不导入（覆盖同名预设时保留本地钩子），allow_hooks 为 true 时才导入
+#[tauri::command]
+pub fn import_presets(
+  app_handle: tauri::AppHandle,
+  path: String,
+  strategy: Option<ConflictStrategy>,
+  component_strategy: Option<ConflictStrategy>,
+  allow_hooks: Option<bool>,
+) -> Result<ImportSummary, String> {
+  let content = std::fs::read_to_string(&path).map_err(|e| format!("读取文件失败：{}", e))?;
+  let bundle: PresetBundle = serde_json::from_str(&content).map_err(|e| format!("文件格式错误：{}", e))?;
+  if bundle.format != EXPORT_FORMAT { return Err("不是预设分享文件".into()); }
+  if bundle.version > EXPORT_VERSION { return Err(format!("分享文件版本 {} 过新，请升级应用", bundle.version)); }
+  for p in &bundle.presets { p.validate()?; }
+  let strategy = strategy.unwrap_or_default();
+  let component_strategy = component_strategy.unwrap_or(strategy);
+  let allow_hooks = allow_hooks.unwrap_or(false);
+  let hooks: Vec<IncomingHook> = bundle
+    .presets
+    .iter()
+    .flat_map(|p| p.hooks.iter().map(|h| IncomingHook { preset: p.name.clone(), command: h.clone() }))
+    .collect();
+
+  // 组件条目（分享文件中的旧格式条目先迁移）
+  let (incoming_reg, _) = ComponentRegistry::from_value(serde_json::json!({ "components": bundle.components }))?;
+  let mut registry = components::read(&app_handle)?;
+  // 与注册表导入共用合并逻辑：每个条目都按 component_strategy 处理
+  let resolutions = incoming_reg.components.iter().map(|c| (c.key.clone(), component_strategy)).collect();
+  let merged = registry_share::merge(&mut registry, incoming_reg.components, MergeStrategy::KeepLocal, &resolutions);
+  let renamed_keys = merged.renamed_keys;
+  let comp_counts = ImportCounts::from(merged.counts);
+
+  // 预设；合并完成后再写入组件注册表，presets.json 无法解析或注册表校验失败时两个文件都不写入
+  let preset_counts = update(&app_handle, |presets| {
+    let mut counts = ImportCounts::default();
+    let now = job::now_millis();
+    for mut incoming in bundle.presets {
+      for f in incoming.features.iter_mut() {
+        if let Some(new_key) = renamed_keys.get(f) { *f = new_key.clone(); }
+      }
+      incoming.updated_at = now;
+      let pos = presets.iter().position(|p| p.name == incoming.name);
+      if !allow_hooks { incoming.hooks = pos.map(|i| presets[i].hooks.clone()).unwrap_or_default(); }
+      let Some(pos) = pos else {
+        if incoming.created_at == 0 { incoming.created_at = now; }
+        presets.push(incoming);
+        counts.added += 1;
+        continue;
+      };
+      let existing = &presets[pos];
+      let same = existing.version == incoming.version && existing.lang == incoming.lang && existing.features == incoming.features
+        && existing.package_manager == incoming.package_manager && existing.hooks == incoming.hooks && existing.directory == incoming.directory;
+      if same { counts.unchanged += 1; continue; }
+      match strategy {
+        ConflictStrategy::Skip => counts.skipped += 1,
+        ConflictStrategy::Overwrite => {
+          incoming.created_at = presets[pos].created_at;
+          presets[pos] = incoming;
+          counts.overwritten += 1;
+        }
+        ConflictStrategy::Rename => {
+          if !allow_hooks { incoming.hooks.clear(); }
+          incoming.name = unique_name(&incoming.name, &|n| presets.iter().any(|p| p.name == n), &|b, n| format!("{} ({})", b, n));
+          incoming.created_at = now;
+          presets.push(incoming);
+          counts.renamed += 1;
+        }
+      }
+    }
+    components::save(&app_handle, registry)?;
+    Ok(counts)
+  })?;
+
+  Ok(ImportSummary { presets: preset_counts, components: comp_counts, renamed_keys, hooks, hooks_imported: allow_hooks })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn export_includes_transitive_requires() {
+    let value = serde_json::json!({ "components": [
+      { "key": "app", "packages": ["app-ui"], "requires": ["mid"] },
+      { "key": "mid", "packages": ["mid-lib"], "requires": ["base"] },
+      { "key": "base", "packages": ["base-lib"] },
+      { "key": "unused", "packages": ["unused-lib"] },
+    ] });
+    let (registry, _) = ComponentRegistry::from_value(value).unwrap();
+    let preset: Preset = serde_json::from_value(serde_json::json!({ "name": "p", "version": "vue3", "lang": "ts", "features": ["app"] })).unwrap();
+    let exported = bundle_components(&registry, &[preset]);
+    let keys: Vec<&str> = exported.iter().filter_map(|c| c["key"].as_str()).collect();
+    assert_eq!(keys, vec!["app", "mid", "base"]);
+    // 导出的条目自成一个可通过校验的注册表
+    let (mut imported, _) = ComponentRegistry::from_value(serde_json::json!({ "components": exported })).unwrap();
+    imported.normalize();
+    assert!(imported.validate().is_empty());
+  }
+}
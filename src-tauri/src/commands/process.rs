@@ -0,0 +1,157 @@
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use super::config::{NetworkSettings, StepPolicy};
+use super::logs::Stream;
+
+// 解析 pnpm 可执行路径（兼容 Windows 上的 pnpm.cmd / pnpm.exe）
+#[allow(unused_variables)]
+pub fn resolve_pnpm() -> Option<String> {
+  #[cfg(windows)]
+  {
+    let try_bin = |bin: &str| -> bool { Command::new(bin).arg("--version").output().ok().is_some() };
+    for c in ["pnpm", "pnpm.cmd", "pnpm.exe"] { if try_bin(c) { return Some(c.to_string()); } }
+    // 兜底：where 定位绝对路径
+    if let Ok(out) = Command::new("where").arg("pnpm").output() {
+      if let Ok(s) = String::from_utf8(out.stdout) {
+        if let Some(path) = s.lines().next() { let p = path.trim().to_string(); if try_bin(&p) { return Some(p); } }
+      }
+    }
+    None
+  }
+  #[cfg(not(windows))]
+  { Some("pnpm".to_string()) }
+}
+
+// 构建 pnpm 命令并注入镜像与代理设置；所有 pnpm 调用都应经由此处创建
+pub fn pnpm_command(pnpm_bin: &str, network: &NetworkSettings) -> Command {
+  let mut cmd = Command::new(pnpm_bin);
+  network.apply_to(&mut cmd);
+  cmd
+}
+
+// 以系统 shell 执行一行命令（预设中的钩子）
+pub fn shell_command(line: &str) -> Command {
+  #[cfg(windows)]
+  { let mut cmd = Command::new("cmd"); cmd.args(["/C", line]); cmd }
+  #[cfg(not(windows))]
+  { let mut cmd = Command::new("sh"); cmd.args(["-c", line]); cmd }
+}
+
+#[derive(Debug)]
+pub enum RunError {
+  Spawn(String),
+  TimedOut(Duration),
+}
+
+impl std::fmt::Display for RunError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      RunError::Spawn(e) => write!(f, "执行失败：{}", e),
+      RunError::TimedOut(d) => write!(f, "执行超时（{} 秒），已终止子进程", d.as_secs()),
+    }
+  }
+}
+
+// 终止整个进程树：pnpm 会再派生 node 子进程，仅 kill 直接子进程会留下孤儿进程
+fn kill_tree(child: &mut Child) {
+  #[cfg(windows)]
+  { let _ = Command::new("taskkill").args(["/T", "/F", "/PID", &child.id().to_string()]).output(); }
+  #[cfg(unix)]
+  { let _ = Command::new("kill").args(["-KILL", "--", &format!("-{}", child.id())]).output(); }
+  let _ = child.kill();
+}
+
+// 逐行读取管道：每行经通道交给调用线程回调，同时保留完整输出
+fn pump<R: Read>(reader: R, stream: Stream, tx: mpsc::Sender<(Stream, String)>) -> Vec<u8> {
+  let mut all = Vec::new();
+  let mut reader = BufReader::new(reader);
+  let mut buf = Vec::new();
+  loop {
+    buf.clear();
+    match reader.read_until(b'\n', &mut buf) {
+      Ok(0) | Err(_) => break,
+      Ok(_) => {
+        all.extend_from_slice(&buf);
+        let line = String::from_utf8_lossy(&buf).trim_end().to_string();
+        if !line.is_empty() { let _ = tx.send((stream, line)); }
+      }
+    }
+  }
+  all
+}
+
+// 运行子进程并采集输出，输出按行实时回调 on_line；超过 timeout 时终止进程树并返回 TimedOut
+pub fn run_with_timeout(cmd: &mut Command, timeout: Option<Duration>, on_line: &dyn Fn(Stream, &str)) -> Result<Output, RunError> {
+  cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+  // 独立进程组，便于超时时整组终止
+  #[cfg(unix)]
+  { use std::os::unix::process::CommandExt; cmd.process_group(0); }
+  let mut child = cmd.spawn().map_err(|e| RunError::Spawn(e.to_string()))?;
+  let stdout = child.stdout.take();
+  let stderr = child.stderr.take();
+  let (tx, rx) = mpsc::channel::<(Stream, String)>();
+
+  std::thread::scope(|s| {
+    // 后台读取管道，避免输出过多时子进程阻塞在写入上
+    let tx_err = tx.clone();
+    let out_h = s.spawn(move || stdout.map(|r| pump(r, Stream::Stdout, tx)).unwrap_or_default());
+    let err_h = s.spawn(move || stderr.map(|r| pump(r, Stream::Stderr, tx_err)).unwrap_or_default());
+    let started = Instant::now();
+    let status = loop {
+      while let Ok((stream, line)) = rx.try_recv() { on_line(stream, &line); }
+      match child.try_wait() {
+        Ok(Some(st)) => break Ok(st),
+        Ok(None) => {
+          if let Some(limit) = timeout {
+            if started.elapsed() >= limit {
+              kill_tree(&mut child);
+              let _ = child.wait();
+              break Err(RunError::TimedOut(limit));
+            }
+          }
+          std::thread::sleep(Duration::from_millis(100));
+        }
+        Err(e) => break Err(RunError::Spawn(e.to_string())),
+      }
+    };
+    let stdout = out_h.join().unwrap_or_default();
+    let stderr = err_h.join().unwrap_or_default();
+    // 读线程结束后发送端全部释放，取出剩余行
+    for (stream, line) in rx.try_iter() { on_line(stream, &line); }
+    status.map(|status| Output { status, stdout, stderr })
+  })
+}
+
+fn timeout_of(policy: &StepPolicy) -> Option<Duration> {
+  if policy.timeout_secs > 0 { Some(Duration::from_secs(policy.timeout_secs)) } else { None }
+}
+
+// 按步骤策略执行命令：非零退出或超时后按退避重试；make 每次重试时重新构建命令
+// 子进程输出逐行交给 log（带 stdout/stderr 标记），执行过程提示以 Info 输出
+pub fn run_with_policy(step: &str, policy: &StepPolicy, log: &dyn Fn(Stream, &str), make: &dyn Fn() -> Command) -> Result<Output, String> {
+  let emit = |line: &str| log(Stream::Info, line);
+  let mut attempt: u32 = 0;
+  loop {
+    let err = match run_with_timeout(&mut make(), timeout_of(policy), log) {
+      Ok(out) if out.status.success() => return Ok(out),
+      Ok(out) => {
+        match out.status.code() {
+          Some(code) => format!("步骤 {} 执行失败（退出码 {}）", step, code),
+          None => format!("步骤 {} 执行失败（进程被终止）", step),
+        }
+      }
+      Err(RunError::TimedOut(d)) => format!("步骤 {} 超时（{} 秒），已终止子进程", step, d.as_secs()),
+      // 无法启动进程（如 pnpm 不存在）时重试无意义，直接返回
+      Err(e @ RunError::Spawn(_)) => return Err(format!("步骤 {} {}", step, e)),
+    };
+    emit(&err);
+    if attempt >= policy.retries { return Err(err); }
+    let wait = policy.backoff_ms.saturating_mul(1u64 << attempt.min(16));
+    attempt += 1;
+    emit(&format!("步骤 {} 将在 {} 毫秒后进行第 {}/{} 次重试", step, wait, attempt, policy.retries));
+    std::thread::sleep(Duration::from_millis(wait));
+  }
+}
@@ -1,249 +1,338 @@
-use tauri::Manager;
-use tauri::Emitter;
-use tauri::async_runtime;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+use tauri::ipc::Channel;
+
+use super::components::{self, FeatureResolution};
+use super::config::{self, Settings, StepPolicy};
+use super::history;
+use super::reporter::{self, CreateEvent, Reporter};
+use super::job::{self, JobState};
+use super::jobs;
+use super::queue;
+use super::process::{pnpm_command, resolve_pnpm, run_with_policy, run_with_timeout, shell_command};
+use super::scaffold_cache;
+use super::templates;
+
+// 创建参数：同时作为任务状态文件中的快照，续跑时原样复用
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateOptions {
+  pub version: String,
+  pub lang: String,
+  pub name: String,
+  pub directory: String,
+  #[serde(default)]
+  pub features: Vec<String>,
+  // 创建完成后在项目目录中依次执行的命令（来自预设）
+  #[serde(default)]
+  pub hooks: Vec<String>,
+  // Vue 3 使用 create-vue（按 ts / router / pinia 选项生成）而非 create-vite 模板；同步新建沿用该脚手架
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub create_vue: bool,
+}
 
-#[tauri::command]
-pub fn check_target_dir(directory: String, name: String) -> Result<(), String> {
-  let base = std::path::Path::new(&directory);
-  let project_dir = base.join(&name);
-  if project_dir.exists() {
-    // 判断是否非空
-    match std::fs::read_dir(&project_dir) {
-      Ok(mut it) => {
-        if it.next().is_some() {
-          return Err(format!(
-            "目标文件夹 \"{}\" 非空（路径：{}），请更换名称或选择空目录。",
-            name,
-            project_dir.display()
-          ));
-        }
-      },
-      Err(_) => {
-        // 无法读取则谨慎处理为不可覆盖
-        return Err(format!(
-          "目标文件夹 \"{}\" 已存在（路径：{}），请更换名称或选择空目录。",
-          name,
-          project_dir.display()
-        ));
+impl CreateOptions {
+  pub fn project_dir(&self) -> PathBuf {
+    Path::new(&self.directory).join(&self.name)
+  }
+}
+
+// 目标目录非空检查；hint 为提示语尾部
+fn ensure_empty_dir(name: &str, project_dir: &Path, hint: &str) -> Result<(), String> {
+  if !project_dir.exists() { return Ok(()); }
+  match std::fs::read_dir(project_dir) {
+    Ok(mut it) => {
+      if it.next().is_some() {
+        return Err(format!("目标文件夹 \"{}\" 非空（路径：{}），{}", name, project_dir.display(), hint));
       }
+    },
+    Err(_) => {
+      // 无法读取则谨慎处理为不可覆盖
+      return Err(format!("目标文件夹 \"{}\" 已存在（路径：{}），{}", name, project_dir.display(), hint));
     }
   }
   Ok(())
 }
 
 #[tauri::command]
-pub fn create_project(
-  app_handle: tauri::AppHandle,
-  version: String,
-  lang: String,
-  name: String,
-  directory: String,
-  set_default: bool,
-  features: Vec<String>,
-) -> Result<(), String> {
-  use std::process::Command;
-
-  // 解析 pnpm 可执行路径（兼容 Windows 上的 pnpm.cmd / pnpm.exe）
-  #[allow(unused_variables)]
-  fn resolve_pnpm() -> Option<String> {
-    #[cfg(windows)]
-    {
-      let try_bin = |bin: &str| -> bool {
-        Command::new(bin)
-          .arg("--version")
-          .output()
-          .ok()
-          .is_some()
-      };
+pub fn check_target_dir(directory: String, name: String) -> Result<(), String> {
+  let project_dir = Path::new(&directory).join(&name);
+  if job::exists(&project_dir) {
+    return Err(format!(
+      "目标文件夹 \"{}\" 中存在未完成的创建任务（路径：{}），可续跑该任务或更换名称。",
+      name,
+      project_dir.display()
+    ));
+  }
+  ensure_empty_dir(&name, &project_dir, "请更换名称或选择空目录。")
+}
 
-      let candidates = ["pnpm", "pnpm.cmd", "pnpm.exe"];
-      for c in candidates {
-        if try_bin(c) {
-          return Some(c.to_string());
-        }
-      }
+// 创建流程中的单个步骤；id 写入任务状态文件用于续跑
+#[derive(Clone, Debug)]
+enum Step {
+  // 离线模式下的预检：确认所需依赖均已在 pnpm store 中
+  Preflight { packages: Vec<String> },
+  Scaffold,
+  Install,
+  AddFeature { key: String, dev: bool, packages: Vec<String> },
+  Configure,
+  Hook { index: usize, command: String },
+}
 
-      // 兜底：where 定位绝对路径
-      if let Ok(out) = Command::new("where").arg("pnpm").output() {
-        if let Ok(s) = String::from_utf8(out.stdout) {
-          if let Some(path) = s.lines().next() {
-            let p = path.trim().to_string();
-            if try_bin(&p) {
-              return Some(p);
-            }
-          }
-        }
-      }
-      None
+impl Step {
+  fn id(&self) -> String {
+    match self {
+      Step::Preflight { .. } => "preflight".into(),
+      Step::Scaffold => "scaffold".into(),
+      Step::Install => "install".into(),
+      Step::AddFeature { key, .. } => format!("add:{}", key),
+      Step::Configure => "configure".into(),
+      Step::Hook { index, .. } => format!("hook:{}", index),
     }
+  }
 
-    #[cfg(not(windows))]
-    {
-      Some("pnpm".to_string())
+  fn title(&self) -> String {
+    match self {
+      Step::Preflight { .. } => "离线预检".into(),
+      Step::Scaffold => "初始化脚手架".into(),
+      Step::Install => "安装依赖".into(),
+      Step::AddFeature { key, .. } => format!("安装特性：{}", key),
+      Step::Configure => "写入项目配置".into(),
+      Step::Hook { command, .. } => format!("执行钩子：{}", command),
     }
   }
+}
 
-  let pnpm_bin = resolve_pnpm().ok_or_else(|| "pnpm not found".to_string())?;
+// 按注册表的 requires / conflicts 解析最终特性集合并写回 opts（依赖在前）；存在冲突时拒绝创建
+pub fn resolve_features(app_handle: &tauri::AppHandle, opts: &mut CreateOptions) -> Result<FeatureResolution, String> {
+  // 注册表读取失败时报错，不能按空注册表把所选特性判为未知
+  let res = components::read(app_handle)?.resolve_features(&opts.features, &opts.version, &opts.lang);
+  if !res.is_ok() { return Err(format!("特性选择有误：\n{}", res.errors.join("\n"))); }
+  opts.features = res.features.clone();
+  Ok(res)
+}
 
-  // optionally persist default directory
-  if set_default {
-    if let Ok(mut cfg_dir) = app_handle.path().app_config_dir() {
-      std::fs::create_dir_all(&cfg_dir).map_err(|e| e.to_string())?;
-      cfg_dir.push("config.json");
-      let payload = serde_json::json!({ "default_directory": directory });
-      std::fs::write(&cfg_dir, payload.to_string()).map_err(|e| e.to_string())?;
+// 根据创建参数与当前注册表生成步骤列表（续跑时重新生成，按 id 跳过已完成步骤）；
+// 注册表读取失败时报错，避免按空注册表漏掉全部特性步骤
+fn plan_steps(app_handle: &tauri::AppHandle, opts: &CreateOptions, offline: bool) -> Result<Vec<Step>, String> {
+  let registry = components::read(app_handle)?;
+  let mut steps = vec![Step::Scaffold, Step::Install];
+  for k in opts.features.iter() {
+    if let Some((dev, packages)) = registry.get(k).and_then(|c| c.packages_for(&opts.version, &opts.lang)) {
+      if !packages.is_empty() { steps.push(Step::AddFeature { key: k.clone(), dev, packages }); }
     }
   }
+  steps.push(Step::Configure);
+  for (index, command) in opts.hooks.iter().enumerate() {
+    if !command.trim().is_empty() { steps.push(Step::Hook { index, command: command.clone() }); }
+  }
+  if offline {
+    let packages = steps.iter().flat_map(|s| match s { Step::AddFeature { packages, .. } => packages.clone(), _ => Vec::new() }).collect();
+    steps.insert(0, Step::Preflight { packages });
+  }
+  Ok(steps)
+}
 
-  // ensure base directory exists & pre-check target dir non-empty
-  let base = std::path::Path::new(&directory);
-  if !base.exists() { std::fs::create_dir_all(&base).map_err(|e| e.to_string())?; }
-  let project_dir = base.join(&name);
-  if project_dir.exists() {
-    // 阻止覆盖：非空则直接终止
-    match std::fs::read_dir(&project_dir) {
-      Ok(mut it) => {
-        if it.next().is_some() {
-          return Err(format!(
-            "目标文件夹 \"{}\" 非空（路径：{}），已终止创建。",
-            name,
-            project_dir.display()
-          ));
-        }
-      },
-      Err(_) => {
-        return Err(format!(
-          "目标文件夹 \"{}\" 已存在（路径：{}），已终止创建。",
-          name,
-          project_dir.display()
-        ));
-      }
-    }
+// 拆分包描述 "name@range"（兼容 "@scope/name@range"）；无版本时返回 None
+pub fn split_spec(spec: &str) -> (&str, Option<&str>) {
+  match spec.rfind('@') {
+    Some(i) if i > 0 => (&spec[..i], Some(&spec[i + 1..])),
+    _ => (spec, None),
   }
+}
 
-  // build command
-  let mut cmd = Command::new(&pnpm_bin);
-  match version.as_str() {
-    "vue3" => {
-      // 使用 create-vue v3：无需显式指定 --packageManager，跟随 runner（pnpm）
-      let mut args = vec!["dlx", "create-vue@latest", &name];
-      if lang == "ts" { args.push("--ts"); }
-      if features.iter().any(|f| f == "router") { args.push("--router"); }
-      if features.iter().any(|f| f == "pinia") { args.push("--pinia"); }
-      // 固定包管理器并禁用交互提示
-      args.push("--packageManager");
-      args.push("pnpm");
-      cmd.current_dir(base).args(args).env("CI", "true");
-    }
-    "vue2" => {
-      // Vue CLI v5 with inline preset to force Vue2 minimal (babel)
-      let inline_preset = serde_json::json!({
-        "vueVersion": "2",
-        "plugins": {"@vue/cli-plugin-babel": {}}
-      }).to_string();
-      cmd.current_dir(base)
-        .args(["dlx", "@vue/cli@5", "create", &name, "--inlinePreset", &inline_preset, "--packageManager", "pnpm", "--no-git", "--force"]);
-    }
-    _ => return Err("unknown version".into()),
+// 离线预检：在临时目录中以 --offline 安装模板与特性所需的全部依赖，缺失时列出 store 中没有的包
+fn offline_preflight(app_handle: &tauri::AppHandle, opts: &CreateOptions, settings: &Settings, pnpm_bin: &str, packages: &[String], rep: &Reporter) -> Result<(), String> {
+  let variant = templates::variant(&opts.version, &opts.lang).ok_or_else(|| "unknown version".to_string())?;
+  let mut deps = templates::dependencies(app_handle, variant)?;
+  for spec in packages {
+    let (name, range) = split_spec(spec);
+    deps.insert(name.to_string(), range.unwrap_or("latest").to_string());
+  }
+  rep.info(&format!("离线预检：检查 {} 个依赖是否已在 pnpm store 中...", deps.len()));
+  let dir = std::env::temp_dir().join(format!("acps-offline-preflight-{}", job::now_millis()));
+  std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+  let manifest = serde_json::json!({ "name": "offline-preflight", "private": true, "dependencies": deps });
+  let result = std::fs::write(dir.join("package.json"), manifest.to_string())
+    .map_err(|e| e.to_string())
+    .and_then(|_| {
+      let mut cmd = pnpm_command(pnpm_bin, &settings.network);
+      cmd.current_dir(&dir).args(["install", "--offline", "--ignore-scripts", "--no-lockfile"]);
+      let timeout = if settings.steps.install.timeout_secs > 0 { Some(Duration::from_secs(settings.steps.install.timeout_secs)) } else { None };
+      run_with_timeout(&mut cmd, timeout, &|_, _| {}).map_err(|e| format!("离线预检{}", e))
+    });
+  let _ = std::fs::remove_dir_all(&dir);
+  let out = result?;
+  if !out.status.success() {
+    let text = format!("{}\n{}", String::from_utf8_lossy(&out.stdout), String::from_utf8_lossy(&out.stderr));
+    let missing: Vec<&str> = text.lines().map(|l| l.trim()).filter(|l| l.contains("ERR_PNPM_NO_OFFLINE")).collect();
+    let detail = if missing.is_empty() { text.trim().lines().last().unwrap_or("").to_string() } else { missing.join("\n") };
+    return Err(format!("离线预检失败：所需依赖不在 pnpm store 中，请联网后先预热 store。\n{}", detail));
   }
+  rep.info("离线预检通过。");
+  Ok(())
+}
 
-  let status = cmd.status().map_err(|e| e.to_string())?;
-  if !status.success() {
-    return Err("project creation failed".into());
+// 联网脚手架：优先复用缓存的脚手架输出，未命中时执行 pnpm dlx 并写入缓存
+fn run_scaffolder(app_handle: &tauri::AppHandle, opts: &CreateOptions, settings: &Settings, pnpm_bin: &str, rep: &Reporter) -> Result<(), String> {
+  let base = Path::new(&opts.directory);
+  let project_dir = opts.project_dir();
+  let (scaffolder, variant) = scaffold_cache::scaffolder_for(opts).ok_or_else(|| "unknown version".to_string())?;
+  let variant = variant.as_str();
+  rep.info("初始化脚手架与依赖安装...");
+
+  let cache_version = if settings.scaffold_cache { scaffold_cache::resolve_version(pnpm_bin, settings, scaffolder) } else { None };
+  if settings.scaffold_cache {
+    if let Some(entry) = scaffold_cache::find(app_handle, scaffolder, cache_version.as_deref(), variant) {
+      rep.info(&format!("使用缓存的脚手架输出：{}@{}（{}）", entry.scaffolder, entry.version, entry.variant));
+      return scaffold_cache::restore(app_handle, &entry, &project_dir, &opts.name);
+    }
   }
 
-  // install dependencies inside created project (safety step)
-  let mut install = Command::new(&pnpm_bin);
-  install.current_dir(&project_dir).arg("install");
-  install.status().map_err(|e| e.to_string())?;
-
-  // 根据 features 追加依赖
-  let project_dir = base.join(&name);
-  let add = |dev: bool, pkgs: &[&str]| -> Result<(), String> {
-    if pkgs.is_empty() { return Ok(()); }
-    for p in pkgs {
-      let mut c = Command::new(&pnpm_bin);
-      c.current_dir(&project_dir);
-      c.arg("add");
-      if dev { c.arg("-D"); }
-      c.arg(p);
-      let st = c.status().map_err(|e| e.to_string())?;
-      if !st.success() { return Err(format!("failed to add dependency: {}", p)); }
+  let make = || {
+    let mut cmd = pnpm_command(pnpm_bin, &settings.network);
+    cmd.current_dir(base);
+    if opts.version == "vue3" && opts.create_vue {
+      // 使用 create-vue：按语言与所选特性传入选项，跟随 runner（pnpm）并禁用交互提示
+      let mut args = vec!["dlx", scaffolder, &opts.name];
+      if opts.lang == "ts" { args.push("--ts"); }
+      if opts.features.iter().any(|f| f == "router") { args.push("--router"); }
+      if opts.features.iter().any(|f| f == "pinia") { args.push("--pinia"); }
+      if project_dir.exists() { args.push("--force"); }
+      args.extend(["--packageManager", "pnpm"]);
+      cmd.args(args).env("CI", "true");
+    } else if opts.version == "vue3" {
+      // 使用 create-vite 非交互模板，避免交互式选择导致卡顿
+      let mut args = vec!["dlx", scaffolder, &opts.name, "--", "--template", variant];
+      // 续跑或重试时目录中已有状态文件或残留文件，需让脚手架覆盖目标目录
+      if project_dir.exists() { args.push("--overwrite"); }
+      cmd.args(args);
+    } else {
+      // Vue CLI v5 with inline preset to force Vue2 minimal (babel)
+      let inline_preset = serde_json::json!({"vueVersion": "2", "plugins": {"@vue/cli-plugin-babel": {}}}).to_string();
+      cmd.args(["dlx", scaffolder, "create", &opts.name, "--inlinePreset", &inline_preset, "--packageManager", "pnpm", "--no-git", "--force"]);
     }
-    Ok(())
+    cmd
   };
+  run_with_policy("scaffold", &settings.steps.scaffold, &|st, l| rep.line(st, l), &make)?;
 
-  // 动态依赖安装：从注册库读取所有选择特性并安装（含版本与 dev 标记）
-  {
-    let mut path = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
-    path.push("components.json");
-    let registry_v = if path.exists() {
-      let s = std::fs::read_to_string(&path).unwrap_or_else(|_| String::new());
-      serde_json::from_str::<serde_json::Value>(&s).unwrap_or_else(|_| serde_json::json!({ "components": [] }))
-    } else { serde_json::json!({ "components": [] }) };
-    if let Some(arr) = registry_v.get("components").and_then(|x| x.as_array()) {
-      for k in features.iter() {
-        if let Some(item) = arr.iter().find(|it| it.get("key").and_then(|x| x.as_str()) == Some(k.as_str())) {
-          let dev = item.get("dev").and_then(|x| x.as_bool()).unwrap_or(false);
-          let s = item.get("supported");
-          let vue_ok = if version == "vue3" { s.and_then(|m| m.get("vue3")).and_then(|x| x.as_bool()).unwrap_or(true) } else { s.and_then(|m| m.get("vue2")).and_then(|x| x.as_bool()).unwrap_or(true) };
-          let lang_ok = if lang == "ts" { s.and_then(|m| m.get("ts")).and_then(|x| x.as_bool()).unwrap_or(true) } else { s.and_then(|m| m.get("js")).and_then(|x| x.as_bool()).unwrap_or(true) };
-          if !vue_ok || !lang_ok { continue; }
-          let mut pkgs: Vec<String> = Vec::new();
-          if let Some(list) = item.get("packages").and_then(|x| x.as_array()) {
-            for p in list {
-              if let Some(name) = p.as_str() {
-                // 版本拼接：若存在 versions 则选择对应版本
-                let ver = if version == "vue3" { item.get("versions").and_then(|m| m.get("vue3")).and_then(|x| x.as_str()) } else { item.get("versions").and_then(|m| m.get("vue2")).and_then(|x| x.as_str()) };
-                if let Some(v) = ver { pkgs.push(format!("{}@{}", name, v)); } else { pkgs.push(name.to_string()); }
-              }
-            }
-          } else if let Some(name) = item.get("package").and_then(|x| x.as_str()) {
-            let ver = if version == "vue3" { item.get("versions").and_then(|m| m.get("vue3")).and_then(|x| x.as_str()) } else { item.get("versions").and_then(|m| m.get("vue2")).and_then(|x| x.as_str()) };
-            if let Some(v) = ver { pkgs.push(format!("{}@{}", name, v)); } else { pkgs.push(name.to_string()); }
-          }
-          if !pkgs.is_empty() {
-            let pkgs_refs: Vec<&str> = pkgs.iter().map(|s| s.as_str()).collect();
-            add(dev, &pkgs_refs)?;
-          }
+  if let Some(version) = &cache_version {
+    match scaffold_cache::store(app_handle, scaffolder, version, variant, &project_dir, &opts.name) {
+      Ok(()) => rep.info(&format!("已缓存脚手架输出：{}（{}）", version, variant)),
+      Err(e) => rep.info(&format!("缓存脚手架输出失败：{}", e)),
+    }
+  }
+  Ok(())
+}
+
+fn run_step(app_handle: &tauri::AppHandle, step: &Step, opts: &CreateOptions, settings: &Settings, pnpm_bin: &str, rep: &Reporter) -> Result<(), String> {
+  let project_dir = opts.project_dir();
+  let policies = &settings.steps;
+  // 离线模式下 install/add 追加 --offline，仅从本地 store 取包
+  let offline_flag: &[&str] = if settings.offline { &["--offline"] } else { &[] };
+  match step {
+    Step::Preflight { packages } => offline_preflight(app_handle, opts, settings, pnpm_bin, packages, rep)?,
+    Step::Scaffold => {
+      if settings.offline {
+        let variant = templates::variant(&opts.version, &opts.lang).ok_or_else(|| "unknown version".to_string())?;
+        rep.info(&format!("离线模式：使用内置模板 {} 生成项目...", variant));
+        templates::render(app_handle, variant, &project_dir, &opts.name)?;
+      } else {
+        run_scaffolder(app_handle, opts, settings, pnpm_bin, rep)?;
+      }
+      rep.info("脚手架创建完成。");
+      if settings.network.write_npmrc {
+        if let Some(content) = settings.network.npmrc_content() {
+          write_project_npmrc(&project_dir, &content)?;
+          rep.info("已写入项目 .npmrc 镜像配置。");
         }
       }
     }
+    Step::Install => {
+      let make = || { let mut c = pnpm_command(pnpm_bin, &settings.network); c.current_dir(&project_dir).arg("install").args(offline_flag); c };
+      run_with_policy("install", &policies.install, &|st, l| rep.line(st, l), &make)?;
+      rep.info("依赖安装完成。");
+    }
+    Step::AddFeature { key, dev, packages } => {
+      for p in packages {
+        let make = || {
+          let mut c = pnpm_command(pnpm_bin, &settings.network);
+          c.current_dir(&project_dir);
+          c.arg("add").args(offline_flag);
+          if *dev { c.arg("-D"); }
+          c.arg(p);
+          c
+        };
+        run_with_policy(&format!("add:{} ({})", key, p), &policies.add, &|st, l| rep.line(st, l), &make)
+          .map_err(|e| format!("failed to add dependency: {}（{}）", p, e))?;
+        rep.info(&format!("已安装依赖：{}{}", if *dev { "(dev) " } else { "" }, p));
+      }
+    }
+    Step::Configure => configure_project(opts)?,
+    Step::Hook { index, command } => {
+      rep.info(&format!("执行钩子：{}", command));
+      let make = || { let mut c = shell_command(command); settings.network.apply_to(&mut c); c.current_dir(&project_dir); c };
+      // 钩子不一定可重复执行，不做重试
+      let policy = StepPolicy { retries: 0, ..policies.add.clone() };
+      run_with_policy(&format!("hook:{}", index), &policy, &|st, l| rep.line(st, l), &make)?;
+    }
+  }
+  Ok(())
+}
+
+// 将镜像配置合并写入项目 .npmrc：已存在的行保持不变，仅追加缺失的行
+fn write_project_npmrc(project_dir: &Path, content: &str) -> Result<(), String> {
+  let path = project_dir.join(".npmrc");
+  let mut current = std::fs::read_to_string(&path).unwrap_or_default();
+  for line in content.lines() {
+    let key = line.split('=').next().unwrap_or("");
+    if current.lines().any(|l| l.trim_start().starts_with(&format!("{}=", key))) { continue; }
+    if !current.is_empty() && !current.ends_with('\n') { current.push('\n'); }
+    current.push_str(line);
+    current.push('\n');
   }
+  std::fs::write(&path, current).map_err(|e| e.to_string())
+}
 
-  // 处理自定义注册的组件库：对未知的 feature key 根据注册表安装对应包
-  // 读取注册表
-  let registry_v = {
-    let mut path = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
-    path.push("components.json");
-    if path.exists() {
-      let s = std::fs::read_to_string(&path).unwrap_or_else(|_| String::new());
-      serde_json::from_str::<serde_json::Value>(&s).unwrap_or_else(|_| serde_json::json!({ "components": [] }))
-    } else { serde_json::json!({ "components": [] }) }
-  };
-  if let Some(arr) = registry_v.get("components").and_then(|x| x.as_array()) {
-    // 已知键集合
-    let known = ["router","pinia","sass","naive-ui","vfonts","xicons"];
-    for k in features.iter() {
-      if known.iter().any(|x| x == k) { continue; }
-      if let Some(item) = arr.iter().find(|it| it.get("key").and_then(|x| x.as_str()) == Some(k.as_str())) {
-        let pkg = item.get("package").and_then(|x| x.as_str()).unwrap_or("");
-        let dev = item.get("dev").and_then(|x| x.as_bool()).unwrap_or(false);
-        // 支持性检查
-        let ok = {
-          let s = item.get("supported");
-          let vue_ok = if version == "vue3" { s.and_then(|m| m.get("vue3")).and_then(|x| x.as_bool()).unwrap_or(true) } else { s.and_then(|m| m.get("vue2")).and_then(|x| x.as_bool()).unwrap_or(true) };
-          let lang_ok = if lang == "ts" { s.and_then(|m| m.get("ts")).and_then(|x| x.as_bool()).unwrap_or(true) } else { s.and_then(|m| m.get("js")).and_then(|x| x.as_bool()).unwrap_or(true) };
-          vue_ok && lang_ok
-        };
-        if !pkg.is_empty() && ok {
-          let _ = add(dev, &[pkg]);
-        }
+// 依次执行步骤：跳过状态中已完成的步骤，每完成一步写入检查点；失败时记录失败步骤后返回
+fn run_pipeline(app_handle: &tauri::AppHandle, opts: &CreateOptions, state: &mut JobState, rep: &Reporter) -> Result<(), String> {
+  let pnpm_bin = resolve_pnpm().ok_or_else(|| "pnpm not found".to_string())?;
+  let settings = config::load_settings(app_handle);
+  let project_dir = opts.project_dir();
+  if settings.offline { rep.info("离线模式已启用。"); }
+  let steps = plan_steps(app_handle, opts, settings.offline)?;
+  rep.plan(&steps.iter().map(|s| (s.id(), s.title())).collect::<Vec<_>>());
+  for step in steps {
+    let id = step.id();
+    if state.is_completed(&id) {
+      rep.info(&format!("跳过已完成步骤：{}", id));
+      rep.step_skipped(&id);
+      continue;
+    }
+    rep.step_started(&id);
+    let res = run_step(app_handle, &step, opts, &settings, &pnpm_bin, rep);
+    rep.step_finished(&id, &res);
+    match res {
+      Ok(()) => {
+        state.mark_completed(&id);
+        if let Err(e) = state.save(&project_dir) { rep.info(&format!("写入任务状态失败：{}", e)); }
+      }
+      Err(e) => {
+        state.mark_failed(&id, &e);
+        if let Err(se) = state.save(&project_dir) { rep.info(&format!("写入任务状态失败：{}", se)); }
+        return Err(e);
       }
     }
   }
+  JobState::remove(&project_dir);
+  Ok(())
+}
 
-  // 配置主入口：注入样式与字体（若选择）
+// 配置主入口：注入样式与字体、pinia 持久化插件，并生成示例文件
+fn configure_project(opts: &CreateOptions) -> Result<(), String> {
+  let project_dir = opts.project_dir();
+  let features = &opts.features;
   let main_ts = project_dir.join("src").join("main.ts");
   let main_js = project_dir.join("src").join("main.js");
   let target_main = if main_ts.exists() { main_ts } else { main_js };
@@ -265,7 +354,7 @@ pub fn create_project(
     }
 
     // 注入 pinia 插件初始化（Vue 3），将 app.use(createPinia()) 替换为带插件的形式
-    if version == "vue3" && features.iter().any(|f| f == "pinia") {
+    if opts.version == "vue3" && features.iter().any(|f| f == "pinia") {
       let new_content = if content.contains("app.use(createPinia())") {
         content.replace(
           "app.use(createPinia())",
@@ -304,10 +393,10 @@ body { background: #fafafa; color: #1f1f1f; }
   }
 
   // 生成 Pinia 示例（Vue 3 且选择 pinia），并使用持久化
-  if version == "vue3" && features.iter().any(|f| f == "pinia") {
+  if opts.version == "vue3" && features.iter().any(|f| f == "pinia") {
     let stores_dir = project_dir.join("src").join("stores");
     std::fs::create_dir_all(&stores_dir).map_err(|e| e.to_string())?;
-    let ext = if lang == "ts" { "ts" } else { "js" };
+    let ext = if opts.lang == "ts" { "ts" } else { "js" };
     let file = stores_dir.join(format!("counter.{}", ext));
     if !file.exists() {
       let content_ts = r#"import { defineStore } from 'pinia'
@@ -333,9 +422,16 @@ export const useCounterStore = defineStore('counter', {
   Ok(())
 }
 
-// 异步后台创建项目：立即返回，后台执行并推送事件日志，避免卡顿
-#[tauri::command]
-pub async fn create_project_async(
+// 新建任务前的目录准备：确保父目录存在且目标目录为空
+fn prepare_target(opts: &CreateOptions) -> Result<(), String> {
+  let base = Path::new(&opts.directory);
+  if !base.exists() { std::fs::create_dir_all(base).map_err(|e| e.to_string())?; }
+  ensure_empty_dir(&opts.name, &opts.project_dir(), "已终止创建。")
+}
+
+// 在异步运行时的线程上执行（而非主线程），排队等待期间不阻塞界面
+#[tauri::command(async)]
+pub fn create_project(
   app_handle: tauri::AppHandle,
   version: String,
   lang: String,
@@ -344,253 +440,127 @@ pub async fn create_project_async(
   set_default: bool,
   features: Vec<String>,
 ) -> Result<(), String> {
-  use std::process::Command;
-
-  // 事件名常量
-  const EVT_LOG: &str = "project:create_log";
-  const EVT_DONE: &str = "project:create_done";
-
-  // 复制参数供后台线程使用
-  let handle = app_handle.clone();
-  let handle_log = handle.clone();
-  let handle_done = handle.clone();
-  let version_c = version.clone();
-  let lang_c = lang.clone();
-  let name_c = name.clone();
-  let dir_c = directory.clone();
-  let features_c = features.clone();
-
-  // 发送日志的便捷函数
-  let send_log = move |msg: String| {
-    let _ = handle_log.emit(EVT_LOG, serde_json::json!({"line": msg}));
-  };
-
-  // 后台阻塞线程执行原逻辑，分阶段推送日志
-  async_runtime::spawn_blocking(move || {
-    let emit = |s: &str| send_log(s.to_string());
-    emit(&format!("开始创建项目：{}", name_c));
-    emit(&format!("目标目录：{}", dir_c));
-    emit(&format!("Vue 版本：{}，语言：{}", version_c, lang_c));
-    if features_c.is_empty() { emit("选择特性：无"); } else { emit(&format!("选择特性：{}", features_c.join(", "))); }
-
-    // 解析 pnpm 可执行路径
-    #[allow(unused_variables)]
-    fn resolve_pnpm() -> Option<String> {
-      #[cfg(windows)]
-      {
-        let try_bin = |bin: &str| -> bool { Command::new(bin).arg("--version").output().ok().is_some() };
-        for c in ["pnpm", "pnpm.cmd", "pnpm.exe"] { if try_bin(c) { return Some(c.to_string()); } }
-        if let Ok(out) = Command::new("where").arg("pnpm").output() {
-          if let Ok(s) = String::from_utf8(out.stdout) {
-            if let Some(path) = s.lines().next() { let p = path.trim().to_string(); if try_bin(&p) { return Some(p); } }
-          }
-        }
-        None
-      }
-      #[cfg(not(windows))]
-      { Some("pnpm".to_string()) }
-    }
-    let pnpm_bin = match resolve_pnpm() {
-      Some(p) => p,
-      None => {
-        let _ = handle_done.emit(EVT_DONE, serde_json::json!({"success": false, "error": "pnpm not found"}));
-        return;
-      }
-    };
-
-    // 持久化默认目录（可选）
-    if set_default {
-      if let Ok(mut cfg_dir) = handle.path().app_config_dir() {
-        let _ = std::fs::create_dir_all(&cfg_dir);
-        cfg_dir.push("config.json");
-        let payload = serde_json::json!({ "default_directory": dir_c });
-        let _ = std::fs::write(&cfg_dir, payload.to_string());
-      }
-    }
-
-    // 目录检查与准备
-    let base = std::path::Path::new(&dir_c);
-    if !base.exists() { let _ = std::fs::create_dir_all(&base); }
-    let project_dir = base.join(&name_c);
-    if project_dir.exists() {
-      match std::fs::read_dir(&project_dir) {
-        Ok(mut it) => {
-          if it.next().is_some() {
-            let _ = handle_done.emit(EVT_DONE, serde_json::json!({
-              "success": false,
-              "error": format!("目标文件夹 \"{}\" 非空（路径：{}），已终止创建。", name_c, project_dir.display())
-            }));
-            return;
-          }
-        },
-        Err(_) => {
-          let _ = handle_done.emit(EVT_DONE, serde_json::json!({
-            "success": false,
-            "error": format!("目标文件夹 \"{}\" 已存在（路径：{}），已终止创建。", name_c, project_dir.display())
-          }));
-          return;
-        }
-      }
-    }
-
-    emit("初始化脚手架与依赖安装...");
-
-    // 构建脚手架命令
-    let mut cmd = Command::new(&pnpm_bin);
-    match version_c.as_str() {
-      "vue3" => {
-        // 使用 create-vite 非交互模板，避免交互式选择导致卡顿
-        let mut args = vec!["dlx", "create-vite@latest", &name_c, "--", "--template"];
-        args.push(if lang_c == "ts" { "vue-ts" } else { "vue" });
-        cmd.current_dir(base).args(args);
-      }
-      "vue2" => {
-        let inline_preset = serde_json::json!({"vueVersion": "2", "plugins": {"@vue/cli-plugin-babel": {}}}).to_string();
-        cmd.current_dir(base).args(["dlx", "@vue/cli@5", "create", &name_c, "--inlinePreset", &inline_preset, "--packageManager", "pnpm", "--no-git", "--force"]);
-      }
-      _ => {
-        let _ = handle_done.emit(EVT_DONE, serde_json::json!({"success": false, "error": "unknown version"}));
-        return;
-      }
-    }
+  let mut opts = CreateOptions { version, lang, name, directory, features, hooks: Vec::new(), create_vue: true };
+  resolve_features(&app_handle, &mut opts)?;
+  // optionally persist default directory
+  if set_default { config::write_default_directory(&app_handle, &opts.directory)?; }
+  // 同样经队列执行（遵守并发上限与先后顺序），在此等待结束
+  let (tx, rx) = std::sync::mpsc::channel();
+  spawn_create_then(app_handle, opts, false, "create", None, false, Box::new(move |r| { let _ = tx.send(r.clone()); }));
+  rx.recv().unwrap_or_else(|_| Err("创建任务异常结束，未返回结果".into()))
+}
 
-    match cmd.output() {
-      Ok(out) => {
-        if out.status.success() {
-          emit("脚手架创建完成。");
-        } else {
-          let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
-          let stdout = String::from_utf8_lossy(&out.stdout).trim().to_string();
-          if !stdout.is_empty() { emit(&format!("脚手架输出：{}", stdout)); }
-          if !stderr.is_empty() { emit(&format!("脚手架错误：{}", stderr)); }
-          let _ = handle_done.emit(EVT_DONE, serde_json::json!({"success": false, "error": "project creation failed"}));
-          return;
-        }
-      }
-      Err(e) => {
-        let _ = handle_done.emit(EVT_DONE, serde_json::json!({"success": false, "error": format!("执行失败：{}", e)}));
-        return;
-      }
-    }
+// 任务结束回调，在队列线程中以创建结果调用
+pub type OnFinish = Box<dyn FnOnce(&Result<(), String>) + Send + 'static>;
 
-    // 安装依赖
-    let mut install = Command::new(&pnpm_bin);
-    install.current_dir(&project_dir).arg("install");
-    let _ = install.status();
-    emit("依赖安装完成。");
-
-    // 追加依赖与文件调整（复用同步实现的核心逻辑，省略错误终止，仅日志提示）
-    let add = |dev: bool, pkgs: &[&str]| -> Result<(), String> {
-      if pkgs.is_empty() { return Ok(()); }
-      for p in pkgs {
-        let mut c = Command::new(&pnpm_bin);
-        c.current_dir(&project_dir);
-        c.arg("add");
-        if dev { c.arg("-D"); }
-        c.arg(p);
-        let st = c.status().map_err(|e| e.to_string())?;
-        if !st.success() { return Err(format!("failed to add dependency: {}", p)); }
-        emit(&format!("已安装依赖：{}{}", if dev { "(dev) " } else { "" }, p));
-      }
-      Ok(())
-    };
-
-    // 动态依赖安装（与同步版相同）
-    let mut reg_path = match handle.path().app_config_dir() { Ok(p) => p, Err(_) => std::path::PathBuf::new() };
-    reg_path.push("components.json");
-    let registry_v = if reg_path.exists() {
-      let s = std::fs::read_to_string(&reg_path).unwrap_or_else(|_| String::new());
-      serde_json::from_str::<serde_json::Value>(&s).unwrap_or_else(|_| serde_json::json!({ "components": [] }))
-    } else { serde_json::json!({ "components": [] }) };
-    if let Some(arr) = registry_v.get("components").and_then(|x| x.as_array()) {
-      for k in features_c.iter() {
-        if let Some(item) = arr.iter().find(|it| it.get("key").and_then(|x| x.as_str()) == Some(k.as_str())) {
-          let dev = item.get("dev").and_then(|x| x.as_bool()).unwrap_or(false);
-          let s = item.get("supported");
-          let vue_ok = if version_c == "vue3" { s.and_then(|m| m.get("vue3")).and_then(|x| x.as_bool()).unwrap_or(true) } else { s.and_then(|m| m.get("vue2")).and_then(|x| x.as_bool()).unwrap_or(true) };
-          let lang_ok = if lang_c == "ts" { s.and_then(|m| m.get("ts")).and_then(|x| x.as_bool()).unwrap_or(true) } else { s.and_then(|m| m.get("js")).and_then(|x| x.as_bool()).unwrap_or(true) };
-          if !vue_ok || !lang_ok { continue; }
-          let mut pkgs: Vec<String> = Vec::new();
-          if let Some(list) = item.get("packages").and_then(|x| x.as_array()) {
-            for p in list { if let Some(name) = p.as_str() {
-              let ver = if version_c == "vue3" { item.get("versions").and_then(|m| m.get("vue3")).and_then(|x| x.as_str()) } else { item.get("versions").and_then(|m| m.get("vue2")).and_then(|x| x.as_str()) };
-              if let Some(v) = ver { pkgs.push(format!("{}@{}", name, v)); } else { pkgs.push(name.to_string()); }
-            } }
-          } else if let Some(name) = item.get("package").and_then(|x| x.as_str()) {
-            let ver = if version_c == "vue3" { item.get("versions").and_then(|m| m.get("vue3")).and_then(|x| x.as_str()) } else { item.get("versions").and_then(|m| m.get("vue2")).and_then(|x| x.as_str()) };
-            if let Some(v) = ver { pkgs.push(format!("{}@{}", name, v)); } else { pkgs.push(name.to_string()); }
-          }
-          if !pkgs.is_empty() { let pkgs_refs: Vec<&str> = pkgs.iter().map(|s| s.as_str()).collect(); let _ = add(dev, &pkgs_refs); }
-        }
-      }
-    }
+// 启动后台新建任务，返回任务 id（同时作为历史记录 id）；channel 为发起方的 IPC 通道
+pub fn spawn_create(app_handle: tauri::AppHandle, opts: CreateOptions, set_default: bool, kind: &str, channel: Option<Channel<CreateEvent>>, broadcast: bool) -> String {
+  spawn_create_then(app_handle, opts, set_default, kind, channel, broadcast, Box::new(|_| {}))
+}
 
-    // 写入样式与示例 store（略去错误终止）
-    let main_ts = project_dir.join("src").join("main.ts");
-    let main_js = project_dir.join("src").join("main.js");
-    let target_main = if main_ts.exists() { main_ts } else { main_js };
-    if target_main.exists() {
-      if let Ok(mut content) = std::fs::read_to_string(&target_main) {
-        let mut prepend_lines: Vec<String> = Vec::new();
-        if features_c.iter().any(|f| f == "sass") { prepend_lines.push("import './styles/main.scss'".into()); }
-        if features_c.iter().any(|f| f == "vfonts") { prepend_lines.push("import 'vfonts/Lato.css'".into()); prepend_lines.push("import 'vfonts/FiraCode.css'".into()); }
-        if features_c.iter().any(|f| f == "pinia") && !content.contains("pinia-plugin-persistedstate") {
-          prepend_lines.push("import persisted from 'pinia-plugin-persistedstate'".into());
-        }
-        let need_update = prepend_lines.iter().any(|l| !content.contains(l));
-        if need_update {
-          let mut header = String::new();
-          for l in prepend_lines { if !content.contains(&l) { header.push_str(&l); header.push('\n'); } }
-          content = format!("{}{}", header, content);
-          let _ = std::fs::write(&target_main, &content);
-        }
-      }
-    }
+// 同 spawn_create，任务结束后调用 on_finish（批量创建据此汇总结果）
+pub fn spawn_create_then(
+  app_handle: tauri::AppHandle,
+  opts: CreateOptions,
+  set_default: bool,
+  kind: &str,
+  channel: Option<Channel<CreateEvent>>,
+  broadcast: bool,
+  on_finish: OnFinish,
+) -> String {
+  let id = job::new_id();
+  let job_id = id.clone();
+  let kind = kind.to_string();
+  // 先登记再入队，调用返回后即可在任务列表中查到
+  jobs::register(&id, &kind, &opts);
+  if let Some(c) = channel { jobs::subscribe(&id, c); }
+
+  // 经队列在后台阻塞线程执行，分阶段推送日志
+  queue::enqueue(&app_handle.clone(), &id, Box::new(move || {
+    let handle = app_handle;
+    let rep = Reporter::new(&handle, &job_id, broadcast);
+    if let Err(e) = history::start(&handle, &job_id, &kind, &opts, rep.log_path()) { rep.info(&format!("写入创建历史失败：{}", e)); }
+    rep.info(&format!("开始创建项目：{}", opts.name));
+    rep.info(&format!("目标目录：{}", opts.directory));
+    rep.info(&format!("Vue 版本：{}，语言：{}", opts.version, opts.lang));
+    if opts.features.is_empty() { rep.info("选择特性：无"); } else { rep.info(&format!("选择特性：{}", opts.features.join(", "))); }
 
-    // 生成样式与 pinia 示例
-    if features_c.iter().any(|f| f == "sass") {
-      let styles_dir = project_dir.join("src").join("styles");
-      let _ = std::fs::create_dir_all(&styles_dir);
-      let main_scss = styles_dir.join("main.scss");
-      if !main_scss.exists() {
-        let scss = r#"$primary-color: #2f54eb;
-body { background: #fafafa; color: #1f1f1f; }
-.app-container { max-width: 1080px; margin: 0 auto; padding: 12px; }
-"#;
-        let _ = std::fs::write(&main_scss, scss);
-      }
+    // 持久化默认目录（可选）
+    if set_default {
+      if let Err(e) = config::write_default_directory(&handle, &opts.directory) { rep.info(&format!("保存默认目录失败：{}", e)); }
     }
 
-    if version_c == "vue3" && features_c.iter().any(|f| f == "pinia") {
-      let stores_dir = project_dir.join("src").join("stores");
-      let _ = std::fs::create_dir_all(&stores_dir);
-      let ext = if lang_c == "ts" { "ts" } else { "js" };
-      let file = stores_dir.join(format!("counter.{}", ext));
-      if !file.exists() {
-        let content_ts = r#"import { defineStore } from 'pinia'
-interface CounterState { count: number }
-export const useCounterStore = defineStore('counter', {
-  state: (): CounterState => ({ count: 0 }),
-  actions: { increment() { this.count++ } },
-  persist: true,
-})
-"#;
-        let content_js = r#"import { defineStore } from 'pinia'
-export const useCounterStore = defineStore('counter', {
-  state: () => ({ count: 0 }),
-  actions: { increment() { this.count++ } },
-  persist: true,
-})
-"#;
-        let payload = if ext == "ts" { content_ts } else { content_js };
-        let _ = std::fs::write(&file, payload);
-      }
-    }
+    // panic 时同样以失败结束，保证 on_finish 被调用（批量创建据此等待）
+    let result = panic::catch_unwind(AssertUnwindSafe(|| prepare_target(&opts).and_then(|_| {
+      let mut state = JobState::new(opts.clone());
+      run_pipeline(&handle, &opts, &mut state, &rep)
+    }))).unwrap_or_else(|p| Err(job::panic_message(p)));
+    if result.is_ok() { rep.info("项目创建成功。"); }
+    let _ = history::finish(&handle, &job_id, &result);
+    rep.done(&result);
+    on_finish(&result);
+  }));
+  id
+}
 
-    emit("项目创建成功。");
-    let _ = handle_done.emit(EVT_DONE, serde_json::json!({"success": true}));
-  });
+// 异步后台创建项目：立即返回任务 id，后台执行并推送日志，避免卡顿
+// 进度经 on_event 通道只发给调用方；未提供通道时广播全局事件（broadcast 可显式控制）
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_project_async(
+  app_handle: tauri::AppHandle,
+  version: String,
+  lang: String,
+  name: String,
+  directory: String,
+  set_default: bool,
+  features: Vec<String>,
+  on_event: Option<Channel<CreateEvent>>,
+  broadcast: Option<bool>,
+) -> Result<String, String> {
+  let mut opts = CreateOptions { version, lang, name, directory, features, hooks: Vec::new(), create_vue: false };
+  resolve_features(&app_handle, &mut opts)?;
+  let broadcast = reporter::broadcast_default(&on_event, broadcast);
+  // 立即返回，前端经通道或事件显示进度
+  Ok(spawn_create(app_handle, opts, set_default, "create", on_event, broadcast))
+}
 
-  // 立即返回，前端订阅事件显示进度
-  Ok(())
-}
\ No newline at end of file
+// 续跑失败的创建任务：读取项目目录中的任务状态，跳过已完成步骤，从失败步骤重试
+#[tauri::command]
+pub async fn resume_project_job(
+  app_handle: tauri::AppHandle,
+  directory: String,
+  name: String,
+  on_event: Option<Channel<CreateEvent>>,
+  broadcast: Option<bool>,
+) -> Result<String, String> {
+  let broadcast = reporter::broadcast_default(&on_event, broadcast);
+  let project_dir = Path::new(&directory).join(&name);
+  let mut state = JobState::load(&project_dir)?
+    .ok_or_else(|| format!("未找到可续跑的创建任务（路径：{}）", project_dir.display()))?;
+  // 与新建相同，按当前注册表解析特性（补全 requires、检查冲突），失败时不续跑
+  resolve_features(&app_handle, &mut state.options)?;
+  let opts = state.options.clone();
+  let id = job::new_id();
+  let job_id = id.clone();
+  // 任务状态文件在运行期间也存在：原任务或另一次续跑仍在排队或运行时拒绝，只续跑已失败（或随应用退出中断）的任务
+  jobs::register_exclusive(&id, "resume", &opts)?;
+  if let Some(c) = on_event { jobs::subscribe(&id, c); }
+
+  queue::enqueue(&app_handle.clone(), &id, Box::new(move || {
+    let handle = app_handle;
+    let rep = Reporter::new(&handle, &job_id, broadcast);
+    if let Err(e) = history::start(&handle, &job_id, "resume", &opts, rep.log_path()) { rep.info(&format!("写入创建历史失败：{}", e)); }
+    rep.info(&format!("续跑创建任务：{}", opts.name));
+    rep.info(&format!("已完成步骤：{}", if state.completed.is_empty() { "无".to_string() } else { state.completed.join(", ") }));
+    if let Some(step) = &state.failed_step { rep.info(&format!("上次失败步骤：{}", step)); }
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| run_pipeline(&handle, &opts, &mut state, &rep)))
+      .unwrap_or_else(|p| Err(job::panic_message(p)));
+    if result.is_ok() { rep.info("项目创建成功。"); }
+    let _ = history::finish(&handle, &job_id, &result);
+    rep.done(&result);
+  }));
+
+  Ok(id)
+}
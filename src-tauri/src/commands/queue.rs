@@ -0,0 +1,56 @@
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use tauri::async_runtime;
+
+use super::config;
+use super::job;
+use super::jobs;
+
+// 创建任务队列：按提交顺序（FIFO）启动，同时运行的任务数不超过设置中的 max_concurrent_jobs
+type Task = Box<dyn FnOnce() + Send + 'static>;
+
+struct Queue {
+  running: usize,
+  pending: VecDeque<(String, Task)>,
+}
+
+static QUEUE: Mutex<Queue> = Mutex::new(Queue { running: 0, pending: VecDeque::new() });
+
+// 占用一个运行名额；任务结束（包括 panic）时释放并启动下一个排队任务
+struct Slot(tauri::AppHandle);
+
+impl Drop for Slot {
+  fn drop(&mut self) {
+    {
+      let mut q = QUEUE.lock().unwrap_or_else(|e| e.into_inner());
+      q.running = q.running.saturating_sub(1);
+    }
+    pump(&self.0);
+  }
+}
+
+// 提交任务：任务需已在登记表中登记（状态为 queued），有空闲名额时立即启动
+pub fn enqueue(app_handle: &tauri::AppHandle, id: &str, task: Task) {
+  QUEUE.lock().unwrap_or_else(|e| e.into_inner()).pending.push_back((id.to_string(), task));
+  pump(app_handle);
+}
+
+// 在名额允许的范围内依次启动排队任务
+pub fn pump(app_handle: &tauri::AppHandle) {
+  let max = config::load_settings(app_handle).max_concurrent_jobs.max(1);
+  let mut q = QUEUE.lock().unwrap_or_else(|e| e.into_inner());
+  while q.running < max {
+    let Some((id, task)) = q.pending.pop_front() else { break };
+    q.running += 1;
+    jobs::set_running(&id);
+    let slot = Slot(app_handle.clone());
+    async_runtime::spawn_blocking(move || {
+      let _slot = slot;
+      // 任务体未自行捕获的 panic：任务以失败结束，不会一直停留在运行中
+      if let Err(p) = panic::catch_unwind(AssertUnwindSafe(task)) {
+        jobs::finish(&id, &Err(job::panic_message(p)));
+      }
+    });
+  }
+}
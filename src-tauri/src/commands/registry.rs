@@ -1,139 +1,318 @@
-use tauri::Manager;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
 use tauri::async_runtime;
-use tokio::time::{timeout, Duration};
 
-fn registry_path(app_handle: &tauri::AppHandle) -> Option<std::path::PathBuf> {
-  app_handle.path().app_config_dir().ok().map(|mut p| { p.push("components.json"); p })
+use super::compat::{self, VueSupport};
+use super::components::{self, Category, CategoryGroup, Component, ComponentRegistry, FeatureResolution, FieldError, Supported, VersionMap};
+use super::config::{self, NetworkSettings};
+use super::job;
+use super::npm::{self, MajorGroup, PackageMeta, SearchResult, VersionFilter};
+use super::presets;
+use super::npm_cache::{self, CachedPackage};
+use super::process::{pnpm_command, resolve_pnpm, run_with_timeout};
+
+#[tauri::command]
+pub fn read_component_registry(app_handle: tauri::AppHandle) -> Result<ComponentRegistry, String> {
+  components::read(&app_handle)
 }
 
+// 保存注册表：接受旧格式（如单个 package 字段），迁移并校验后写入；
+// 新增的条目标记为个人条目（local），同步共享注册表时保留。现有注册表读取失败时报错，以免把同步来的条目误标为个人条目
 #[tauri::command]
-pub fn read_component_registry(app_handle: tauri::AppHandle) -> Result<serde_json::Value, String> {
-  let path = registry_path(&app_handle).ok_or_else(|| "config dir not available".to_string())?;
-  if !path.exists() {
-    // 默认内容
-    let default = serde_json::json!({
-      "components": [
-        {
-          "key": "router",
-          "label": "vue-router",
-          "packages": ["vue-router"],
-          "desc": "路由管理",
-          "versions": { "vue2": "^3.x", "vue3": "^4.6.3" },
-          "supported": { "vue2": true, "vue3": true, "ts": true, "js": true },
-          "dev": false
-        },
-        {
-          "key": "pinia",
-          "label": "pinia",
-          "packages": ["pinia", "pinia-plugin-persistedstate"],
-          "desc": "轻量状态管理",
-          "versions": { "vue3": "^2.3.1" },
-          "supported": { "vue2": true, "vue3": true, "ts": true, "js": true },
-          "dev": false
-        },
-        {
-          "key": "sass",
-          "label": "sass",
-          "packages": ["sass", "sass-loader"],
-          "desc": "CSS 预处理器",
-          "versions": { "vue2": "^1.94.0", "vue3": "^1.94.0" },
-          "supported": { "vue2": true, "vue3": true, "ts": true, "js": true },
-          "dev": true
-        },
-        {
-          "key": "naive-ui",
-          "label": "naive-ui",
-          "packages": ["naive-ui"],
-          "desc": "Vue 3 组件库",
-          "supported": { "vue2": false, "vue3": true, "ts": true, "js": false }
-        },
-        {
-          "key": "vfonts",
-          "label": "vfonts",
-          "packages": ["vfonts"],
-          "desc": "网页与代码字体",
-          "supported": { "vue2": true, "vue3": true, "ts": true, "js": true }
-        },
-        {
-          "key": "xicons",
-          "label": "@vicons/ionicons5",
-          "packages": ["@vicons/ionicons5"],
-          "desc": "图标库",
-          "supported": { "vue2": true, "vue3": true, "ts": true, "js": true }
-        }
-      ]
-    });
-    if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
-    std::fs::write(&path, default.to_string()).map_err(|e| e.to_string())?;
-    return Ok(default);
+pub fn save_component_registry(app_handle: tauri::AppHandle, payload: serde_json::Value) -> Result<(), String> {
+  let (mut registry, _) = ComponentRegistry::from_value(payload)?;
+  let existing = components::read(&app_handle)?;
+  for c in registry.components.iter_mut() {
+    if existing.get(c.key.trim()).is_none() { c.local = true; }
   }
-  let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
-  let v: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-  Ok(v)
+  components::save(&app_handle, registry)
 }
 
+// 仅校验不保存，返回字段级错误列表（为空表示通过）
 #[tauri::command]
-pub fn save_component_registry(app_handle: tauri::AppHandle, payload: serde_json::Value) -> Result<(), String> {
-  let path = registry_path(&app_handle).ok_or_else(|| "config dir not available".to_string())?;
-  if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
-  std::fs::write(&path, payload.to_string()).map_err(|e| e.to_string())?;
-  Ok(())
+pub fn validate_component_registry(payload: serde_json::Value) -> Result<Vec<FieldError>, String> {
+  let (mut registry, _) = ComponentRegistry::from_value(payload)?;
+  registry.normalize();
+  Ok(registry.validate())
+}
+
+// 按分类分组并排序的注册表，供特性选择界面使用
+#[tauri::command]
+pub fn read_component_groups(app_handle: tauri::AppHandle) -> Result<Vec<CategoryGroup>, String> {
+  Ok(components::read(&app_handle)?.grouped())
 }
 
-// 通过 pnpm 查询 npm 包所有版本（动态）
+// 检查特性选择：展开 requires、检查 conflicts 与单选组，返回最终特性与错误说明（不抛错，便于前端逐条展示）
 #[tauri::command]
-pub async fn fetch_npm_versions(package: String) -> Result<Vec<String>, String> {
-  use std::process::Command;
-
-  #[allow(unused_variables)]
-  fn resolve_pnpm() -> Option<String> {
-    #[cfg(windows)]
-    {
-      let try_bin = |bin: &str| -> bool {
-        Command::new(bin).arg("--version").output().ok().is_some()
-      };
-      for c in ["pnpm", "pnpm.cmd", "pnpm.exe"] { if try_bin(c) { return Some(c.to_string()); } }
-      if let Ok(out) = Command::new("where").arg("pnpm").output() {
-        if let Ok(s) = String::from_utf8(out.stdout) {
-          if let Some(path) = s.lines().next() { let p = path.trim().to_string(); if try_bin(&p) { return Some(p); } }
-        }
-      }
-      None
+pub fn check_features(app_handle: tauri::AppHandle, version: String, lang: String, features: Vec<String>) -> Result<FeatureResolution, String> {
+  Ok(components::read(&app_handle)?.resolve_features(&features, &version, &lang))
+}
+
+// 包元数据查询结果：cached 表示来自本地缓存，stale 表示缓存已过期（查询失败时的兜底），error 为本次查询的错误
+#[derive(Clone, Debug, Serialize)]
+pub struct PackageInfo {
+  pub meta: PackageMeta,
+  pub fetched_at: u64,
+  pub cached: bool,
+  pub stale: bool,
+  pub error: Option<String>,
+}
+
+// 版本列表：versions 为过滤后的扁平列表（semver 从高到低），majors 为按主版本的分组
+#[derive(Clone, Debug, Serialize)]
+pub struct NpmVersions {
+  pub package: String,
+  pub versions: Vec<String>,
+  pub majors: Vec<MajorGroup>,
+  pub dist_tags: BTreeMap<String, String>,
+  pub fetched_at: u64,
+  pub cached: bool,
+  pub stale: bool,
+  pub error: Option<String>,
+}
+
+// 查询包元数据：有效期内直接返回缓存；refresh 为 true 时强制重新查询；
+// 查询失败或超时时返回过期缓存（stale），没有缓存才报错
+pub async fn load_package(app_handle: &tauri::AppHandle, package: &str, refresh: bool) -> Result<PackageInfo, String> {
+  let settings = config::load_settings(app_handle);
+  let package = package.trim().to_string();
+  if package.is_empty() { return Err("包名不能为空".into()); }
+  let registry = npm::registry_for(&settings.network, &package);
+  let cached = npm_cache::read(app_handle, &package, &registry);
+  let from_cache = |c: CachedPackage, stale: bool, error: Option<String>| PackageInfo {
+    meta: c.meta, fetched_at: c.fetched_at, cached: true, stale, error,
+  };
+  if let Some(c) = cached.clone().filter(|c| !refresh && c.is_fresh(settings.npm_cache_ttl_secs)) {
+    return Ok(from_cache(c, false, None));
+  }
+
+  // 将阻塞的 HTTP 请求放到阻塞线程池；超时沿用版本查询（view）的设置
+  let policy = settings.steps.view.clone();
+  let timeout = if policy.timeout_secs > 0 { Some(Duration::from_secs(policy.timeout_secs)) } else { None };
+  let name = package.clone();
+  let result = async_runtime::spawn_blocking(move || npm::fetch_package(&settings.network, &name, timeout))
+    .await
+    .map_err(|e| e.to_string())?;
+  match result {
+    Ok(meta) => {
+      let entry = CachedPackage { package, registry, fetched_at: job::now_millis(), meta };
+      if let Err(e) = npm_cache::write(app_handle, &entry) { log::warn!("写入 npm 缓存失败：{}", e); }
+      Ok(PackageInfo { meta: entry.meta, fetched_at: entry.fetched_at, cached: false, stale: false, error: None })
     }
-    #[cfg(not(windows))]
-    { Some("pnpm".to_string()) }
+    Err(e) => match cached {
+      Some(c) => Ok(from_cache(c, true, Some(e))),
+      None => Err(e),
+    },
   }
+}
 
-  let pnpm_bin = resolve_pnpm().ok_or_else(|| "pnpm not found".to_string())?;
+// 包的完整元数据：版本、dist-tags、发布时间、peerDependencies 与弃用信息
+#[tauri::command]
+pub async fn fetch_package_meta(app_handle: tauri::AppHandle, package: String, refresh: Option<bool>) -> Result<PackageInfo, String> {
+  load_package(&app_handle, &package, refresh.unwrap_or(false)).await
+}
 
-  // 将阻塞的子进程调用放到阻塞线程池，并加超时避免卡住
-  let pkg = package.clone();
-  let bin = pnpm_bin.clone();
-  let handle = async_runtime::spawn_blocking(move || {
-    Command::new(&bin)
-      .args(["view", &pkg, "versions", "--json"])
-      .output()
-  });
-
-  // 先处理超时与 JoinError，再处理子进程的 io::Error
-  let output_res = timeout(Duration::from_secs(8), handle)
+// 查询 npm 包版本列表，供版本下拉框使用：默认排除预发布与已弃用版本；
+// range 为 npm 范围或 dist-tag（如注册表中的 versions.vue2），只返回满足的版本
+#[tauri::command]
+pub async fn fetch_npm_versions(
+  app_handle: tauri::AppHandle,
+  package: String,
+  refresh: Option<bool>,
+  include_prerelease: Option<bool>,
+  include_deprecated: Option<bool>,
+  range: Option<String>,
+) -> Result<NpmVersions, String> {
+  let info = load_package(&app_handle, &package, refresh.unwrap_or(false)).await?;
+  let filter = VersionFilter {
+    include_prerelease: include_prerelease.unwrap_or(false),
+    include_deprecated: include_deprecated.unwrap_or(false),
+    range,
+  };
+  let entries = info.meta.list_versions(&filter)?;
+  let versions = entries.iter().map(|e| e.version.clone()).collect();
+  Ok(NpmVersions {
+    package: info.meta.name,
+    versions,
+    majors: npm::group_by_major(entries),
+    dist_tags: info.meta.dist_tags,
+    fetched_at: info.fetched_at,
+    cached: info.cached,
+    stale: info.stale,
+    error: info.error,
+  })
+}
+
+// 搜索 npm 包（默认 registry 的搜索接口），供注册表编辑器选择要添加的组件
+#[tauri::command]
+pub async fn search_npm_packages(app_handle: tauri::AppHandle, query: String, size: Option<usize>) -> Result<Vec<SearchResult>, String> {
+  let text = query.trim().to_string();
+  if text.is_empty() { return Err("搜索内容不能为空".into()); }
+  let settings = config::load_settings(&app_handle);
+  let policy = &settings.steps.view;
+  let timeout = if policy.timeout_secs > 0 { Some(Duration::from_secs(policy.timeout_secs)) } else { None };
+  let size = size.unwrap_or(20).clamp(1, 250);
+  async_runtime::spawn_blocking(move || npm::search(&settings.network, &text, size, timeout))
     .await
-    .map_err(|_| "查询 npm 版本超时（8s）".to_string())?
-    .map_err(|e| format!("执行 pnpm 失败: {e}"))?; // JoinError
-  let output = output_res.map_err(|e| format!("执行 pnpm 失败: {e}"))?; // io::Error
+    .map_err(|e| e.to_string())?
+}
 
-  if !output.status.success() {
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    return Err(format!("查询版本失败：{}", stderr.trim()));
+// 由包名与关键词推断分类
+// 名称与关键词按 "/"、"-"、"." 等分隔为片段，按整段匹配（避免 "@headlessui/vue" 因含 "less" 被误判）
+fn segments(text: &str) -> Vec<String> {
+  text.to_lowercase().split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()).map(|w| w.to_string()).collect()
+}
+
+// 多段的词（如 "test-utils"）须在同一名称或关键词中连续出现
+fn has_any(sources: &[Vec<String>], terms: &[&str]) -> bool {
+  terms.iter().any(|t| {
+    let parts = segments(t);
+    sources.iter().any(|s| s.windows(parts.len()).any(|w| w == parts.as_slice()))
+  })
+}
+
+fn infer_category(name: &str, keywords: &[String]) -> Category {
+  let sources: Vec<Vec<String>> = std::iter::once(name).chain(keywords.iter().map(|k| k.as_str())).map(segments).collect();
+  let any = |terms: &[&str]| has_any(&sources, terms);
+  if any(&["vitest", "jest", "test-utils", "testing", "cypress", "playwright"]) {
+    Category::Testing
+  } else if any(&["icon", "icons", "iconify", "iconfont"]) {
+    Category::Icons
+  } else if any(&["pinia", "vuex", "state", "state-management"]) {
+    Category::State
+  } else if any(&["sass", "scss", "less", "stylus", "postcss", "tailwind", "tailwindcss", "unocss", "css", "font", "fonts", "fontsource"]) {
+    Category::Styling
+  } else if any(&["eslint", "prettier", "lint", "vite-plugin", "unplugin", "loader", "router", "i18n"]) {
+    Category::Tooling
+  } else if any(&["ui", "components", "design", "component library"]) {
+    Category::Ui
+  } else {
+    Category::Other
   }
+}
+
+// 构建期工具、类型声明与测试库按 devDependencies 安装
+fn infer_dev(name: &str, category: Category) -> bool {
+  category == Category::Testing
+    || name.to_lowercase().starts_with("@types/")
+    || has_any(&[segments(name)], &["eslint", "prettier", "stylelint", "vite-plugin", "unplugin", "loader", "typescript", "sass", "less", "postcss", "autoprefixer"])
+}
+
+// 包名转为组件 key：去掉作用域，非字母数字替换为 "-"
+fn draft_key(name: &str) -> String {
+  let base = name.rsplit('/').next().unwrap_or(name).to_lowercase();
+  let key: String = base.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '-' }).collect();
+  let key = key.trim_matches('-').to_string();
+  if key.is_empty() { "component".into() } else { key }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ComponentDraft {
+  pub component: Component,
+  // 推断所依据的包版本（latest）
+  pub version: String,
+  pub warnings: Vec<String>,
+}
+
+// 由搜索结果生成预填的注册表条目（不保存）：label、desc、packages、dev 与分类按包信息推断，
+// supported / versions 按 latest 版本对 vue 的 peerDependencies 推断
+#[tauri::command]
+pub async fn draft_component(app_handle: tauri::AppHandle, package: String) -> Result<ComponentDraft, String> {
+  let info = load_package(&app_handle, &package, false).await?;
+  let mut warnings: Vec<String> = Vec::new();
+  if let Some(e) = &info.error { warnings.push(format!("{}：使用过期缓存（{}）", package, e)); }
+  let meta = &info.meta;
+  let latest = meta
+    .dist_tags
+    .get("latest")
+    .and_then(|v| meta.versions.iter().find(|m| &m.version == v))
+    .or_else(|| meta.versions.first())
+    .ok_or_else(|| format!("{} 没有可用版本", meta.name))?;
 
-  let stdout = String::from_utf8(output.stdout).map_err(|e| e.to_string())?;
-  let mut versions: Vec<String> = match serde_json::from_str::<serde_json::Value>(&stdout) {
-    Ok(serde_json::Value::Array(arr)) => arr.into_iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect(),
-    Ok(_) => Vec::new(),
-    Err(_) => Vec::new(),
+  let registry = components::load(&app_handle);
+  let base = draft_key(&meta.name);
+  let taken = |k: &str| registry.get(k).is_some();
+  let key = if taken(&base) { presets::unique_name(&base, &taken, &|b, n| format!("{}-{}", b, n)) } else { base };
+  let category = infer_category(&meta.name, &meta.keywords);
+  let mut component = Component {
+    key,
+    label: meta.name.clone(),
+    packages: vec![meta.name.clone()],
+    desc: meta.description.clone().map(|d| d.trim().to_string()).filter(|d| !d.is_empty()),
+    versions: VersionMap::default(),
+    supported: Supported::default(),
+    dev: infer_dev(&meta.name, category),
+    requires: Vec::new(),
+    conflicts: Vec::new(),
+    category,
+    // UI 组件库默认互斥，与内置条目使用同一单选组
+    group: (category == Category::Ui).then(|| "ui-library".to_string()),
+    // 手动添加的条目属于个人配置，同步共享注册表时保留
+    local: true,
+    extra: serde_json::Map::new(),
   };
-  versions.reverse();
-  if versions.len() > 100 { versions.truncate(100); }
-  Ok(versions)
-}
\ No newline at end of file
+
+  let vue = compat::vue_versions(&app_handle, &mut warnings).await?;
+  let support = VueSupport::infer(meta, latest, &vue);
+  if support.vue2.is_none() {
+    warnings.push(format!("{}@{} 未声明 vue 的 peerDependencies，默认同时支持 Vue 2 与 Vue 3", meta.name, latest.version));
+  }
+  support.apply_to(&mut component.supported, &mut component.versions);
+  Ok(ComponentDraft { component, version: latest.version.clone(), warnings })
+}
+
+// 校验镜像与代理设置：返回 pnpm 实际生效的 registry，并尝试查询指定包（默认 vue）的最新版本
+// network 为空时使用已保存的设置，便于在保存前先行验证（例如指向本地替身 registry）
+#[tauri::command]
+pub async fn check_registry_settings(
+  app_handle: tauri::AppHandle,
+  network: Option<NetworkSettings>,
+  package: Option<String>,
+) -> Result<serde_json::Value, String> {
+  let pnpm_bin = resolve_pnpm().ok_or_else(|| "pnpm not found".to_string())?;
+  let settings = config::load_settings(&app_handle);
+  let network = network.unwrap_or(settings.network);
+  let policy = settings.steps.view;
+  let package = package.filter(|p| !p.trim().is_empty()).unwrap_or_else(|| "vue".to_string());
+  let timeout = if policy.timeout_secs > 0 { Some(Duration::from_secs(policy.timeout_secs)) } else { None };
+
+  async_runtime::spawn_blocking(move || {
+    let text = |out: std::process::Output| String::from_utf8_lossy(&out.stdout).trim().to_string();
+    let registry = run_with_timeout(pnpm_command(&pnpm_bin, &network).args(["config", "get", "registry"]), timeout, &|_, _| {})
+      .map_err(|e| e.to_string())
+      .map(text)?;
+    let out = run_with_timeout(pnpm_command(&pnpm_bin, &network).args(["view", &package, "version"]), timeout, &|_, _| {})
+      .map_err(|e| format!("访问 registry 失败：{}", e))?;
+    if !out.status.success() {
+      let stderr = String::from_utf8_lossy(&out.stderr);
+      return Err(format!("访问 registry 失败（{}）：{}", registry, stderr.trim()));
+    }
+    Ok(serde_json::json!({ "registry": registry, "package": package, "version": text(out) }))
+  })
+  .await
+  .map_err(|e| format!("执行 pnpm 失败: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn infer_matches_whole_segments() {
+    let none: Vec<String> = Vec::new();
+    assert!(!infer_dev("@headlessui/vue", infer_category("@headlessui/vue", &none)));
+    assert_eq!(infer_category("@headlessui/vue", &none), Category::Other);
+    assert_eq!(infer_category("lessons-vue", &none), Category::Other);
+    assert_eq!(infer_category("vue-statement", &none), Category::Other);
+    assert_eq!(infer_category("lexicon", &none), Category::Other);
+    assert!(infer_dev("less", Category::Styling));
+    assert!(infer_dev("less-loader", Category::Styling));
+    assert!(infer_dev("vite-plugin-inspect", Category::Tooling));
+    assert!(infer_dev("@types/node", Category::Other));
+    assert_eq!(infer_category("@vue/test-utils", &none), Category::Testing);
+    assert_eq!(infer_category("@iconify/vue", &none), Category::Icons);
+    assert_eq!(infer_category("pinia", &none), Category::State);
+    assert_eq!(infer_category("naive", &["Component Library".to_string()]), Category::Ui);
+  }
+}
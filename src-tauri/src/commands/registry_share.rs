@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::components::{self, Component, ComponentRegistry, SCHEMA_VERSION};
+use super::presets::{self, ConflictStrategy};
+
+// 组件注册表的分享：导出为 components.json 格式的文件，导入时按策略与本地合并
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+  // 冲突时保留本地条目
+  #[default]
+  KeepLocal,
+  // 冲突时使用导入的条目
+  TakeIncoming,
+  // 逐个决定：存在未决冲突时不写入，返回冲突列表，由界面给出 resolutions 后再次导入
+  Ask,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MergeCounts {
+  pub added: usize,
+  pub updated: usize,
+  pub renamed: usize,
+  pub skipped: usize,
+  // 与本地内容完全一致
+  pub unchanged: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ComponentConflict {
+  pub key: String,
+  pub local: Component,
+  pub incoming: Component,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RegistryImport {
+  // 是否已写入 components.json；存在未决冲突时为 false，counts 为预览
+  pub applied: bool,
+  pub counts: MergeCounts,
+  pub conflicts: Vec<ComponentConflict>,
+  // 改名导入时 key 的映射（旧 -> 新）
+  pub renamed_keys: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RegistryExport {
+  pub path: String,
+  pub components: usize,
+  // 因 requires 被一并导出的组件
+  pub added_requires: Vec<String>,
+}
+
+// 目录视为其中的 components.json
+fn registry_file(path: &str) -> PathBuf {
+  let p = Path::new(path);
+  if p.is_dir() { p.join("components.json") } else { p.to_path_buf() }
+}
+
+// 读取外部注册表：components.json（含旧格式）或预设分享文件中的组件条目
+pub fn read_file(path: &str) -> Result<ComponentRegistry, String> {
+  let file = registry_file(path);
+  let content = std::fs::read_to_string(&file).map_err(|e| format!("读取 {} 失败：{}", file.display(), e))?;
+  let mut value: serde_json::Value = serde_json::from_str(&content).map_err(|e| format!("文件格式错误：{}", e))?;
+  if value.get("format").and_then(|f| f.as_str()) == Some(presets::EXPORT_FORMAT) {
+    value = serde_json::json!({ "components": value.get("components").cloned().unwrap_or_default() });
+  }
+  let (mut registry, _) = ComponentRegistry::from_value(value)?;
+  registry.normalize();
+  Ok(registry)
+}
+
+// 按策略把 incoming 合并进 local；Ask 且无对应 resolution 的冲突条目不合并，列入 conflicts。
+// 新条目属于个人条目（local），覆盖已有条目时沿用原标记；改名后，导入条目中引用旧 key 的 requires / conflicts 一并改写
+pub fn merge(
+  local: &mut ComponentRegistry,
+  incoming: Vec<Component>,
+  strategy: MergeStrategy,
+  resolutions: &BTreeMap<String, ConflictStrategy>,
+) -> RegistryImport {
+  let mut counts = MergeCounts::default();
+  let mut conflicts: Vec<ComponentConflict> = Vec::new();
+  let mut renamed_keys = BTreeMap::new();
+  // 写入了导入内容的位置
+  let mut merged: Vec<usize> = Vec::new();
+  for mut item in incoming {
+    let key = item.key.clone();
+    item.local = local.get(&key).is_none_or(|c| c.local);
+    let Some(pos) = local.components.iter().position(|c| c.key == key) else {
+      local.components.push(item);
+      merged.push(local.components.len() - 1);
+      counts.added += 1;
+      continue;
+    };
+    if local.components[pos] == item { counts.unchanged += 1; continue; }
+    let decision = match (resolutions.get(&key), strategy) {
+      (Some(r), _) => *r,
+      (None, MergeStrategy::KeepLocal) => ConflictStrategy::Skip,
+      (None, MergeStrategy::TakeIncoming) => ConflictStrategy::Overwrite,
+      (None, MergeStrategy::Ask) => {
+        conflicts.push(ComponentConflict { key, local: local.components[pos].clone(), incoming: item });
+        continue;
+      }
+    };
+    match decision {
+      ConflictStrategy::Skip => counts.skipped += 1,
+      ConflictStrategy::Overwrite => { local.components[pos] = item; merged.push(pos); counts.updated += 1; }
+      ConflictStrategy::Rename => {
+        let new_key = presets::unique_name(&key, &|k| local.components.iter().any(|c| c.key == k), &|b, n| format!("{}-{}", b, n));
+        item.key = new_key.clone();
+        // 改名后是新条目，属于个人条目
+        item.local = true;
+        local.components.push(item);
+        merged.push(local.components.len() - 1);
+        renamed_keys.insert(key, new_key);
+        counts.renamed += 1;
+      }
+    }
+  }
+  for pos in merged {
+    let c = &mut local.components[pos];
+    for k in c.requires.iter_mut().chain(c.conflicts.iter_mut()) {
+      if let Some(new_key) = renamed_keys.get(k) { *k = new_key.clone(); }
+    }
+  }
+  RegistryImport { applied: false, counts, conflicts, renamed_keys }
+}
+
+// 导出注册表（keys 为空时导出全部）；选中组件 requires 的组件一并导出，保证导入后可通过校验
+#[tauri::command]
+pub fn export_component_registry(app_handle: tauri::AppHandle, path: String, keys: Option<Vec<String>>) -> Result<RegistryExport, String> {
+  let registry = components::read(&app_handle)?;
+  let keys = keys.filter(|k| !k.is_empty());
+  let mut added_requires: Vec<String> = Vec::new();
+  let selected: Vec<Component> = match keys {
+    None => registry.components.clone(),
+    Some(keys) => {
+      for k in &keys {
+        if registry.get(k).is_none() { return Err(format!("未找到组件：{}", k)); }
+      }
+      let wanted = registry.with_requires(&keys);
+      added_requires = wanted.iter().filter(|k| !keys.contains(k)).cloned().collect();
+      registry.components.iter().filter(|c| wanted.contains(&c.key)).cloned().collect()
+    }
+  };
+  let out = ComponentRegistry { schema_version: SCHEMA_VERSION, components: selected };
+  let content = serde_json::to_string_pretty(&out).map_err(|e| e.to_string())?;
+  std::fs::write(&path, content).map_err(|e| format!("写入文件失败：{}", e))?;
+  Ok(RegistryExport { path, components: out.components.len(), added_requires })
+}
+
+// 导入注册表文件（或包含 components.json 的目录）并与本地合并；
+// resolutions 为逐个决定的结果（key -> skip / overwrite / rename），优先于 strategy
+#[tauri::command]
+pub fn import_component_registry(
+  app_handle: tauri::AppHandle,
+  path: String,
+  strategy: Option<MergeStrategy>,
+  resolutions: Option<BTreeMap<String, ConflictStrategy>>,
+) -> Result<RegistryImport, String> {
+  let incoming = read_file(&path)?;
+  let mut registry = components::read(&app_handle)?;
+  let mut result = merge(&mut registry, incoming.components, strategy.unwrap_or_default(), &resolutions.unwrap_or_default());
+  if !result.conflicts.is_empty() { return Ok(result); }
+  components::save(&app_handle, registry)?;
+  result.applied = true;
+  Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn item(key: &str, label: &str, requires: &[&str], conflicts: &[&str]) -> Component {
+    serde_json::from_value(serde_json::json!({ "key": key, "label": label, "requires": requires, "conflicts": conflicts })).unwrap()
+  }
+
+  #[test]
+  fn rename_rewrites_incoming_references() {
+    let mut local = ComponentRegistry { schema_version: SCHEMA_VERSION, components: vec![item("base", "本地", &[], &[]), item("user", "本地", &["base"], &[])] };
+    let incoming = vec![item("base", "导入", &[], &[]), item("plugin", "导入", &["base"], &["base"])];
+    let resolutions = BTreeMap::from([("base".to_string(), ConflictStrategy::Rename)]);
+    let result = merge(&mut local, incoming, MergeStrategy::KeepLocal, &resolutions);
+    assert_eq!(result.renamed_keys.get("base").map(String::as_str), Some("base-2"));
+    let plugin = local.get("plugin").unwrap();
+    assert_eq!(plugin.requires, vec!["base-2"]);
+    assert_eq!(plugin.conflicts, vec!["base-2"]);
+    assert!(plugin.local);
+    // 本地条目的引用保持不变
+    assert_eq!(local.get("user").unwrap().requires, vec!["base"]);
+    assert_eq!(local.get("base").unwrap().label, "本地");
+  }
+}
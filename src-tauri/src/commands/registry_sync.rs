@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use tauri::async_runtime;
+use tauri::Manager;
+
+use super::components::{self, ComponentRegistry};
+use super::config::{self, RegistrySource, RegistrySourceKind, Settings};
+use super::job;
+use super::npm;
+use super::process::run_with_timeout;
+
+// 从团队共享来源同步组件注册表：来源中的条目为准，标记为 local 的个人条目保留
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SyncCounts {
+  pub added: usize,
+  pub updated: usize,
+  // 来源中已不存在而删除的条目
+  pub removed: usize,
+  // 与来源同 key 的个人条目，保留本地内容
+  pub kept_local: usize,
+  pub unchanged: usize,
+}
+
+// 最近一次同步的记录（应用配置目录/registry-sync.json）
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SyncState {
+  pub source: RegistrySource,
+  pub synced_at: u64,
+  // git 为提交哈希；HTTP 优先使用 ETag；其余为内容哈希
+  pub revision: String,
+  pub counts: SyncCounts,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SyncReport {
+  // 是否已写入；预览时为 false，removed_keys 等为将要发生的变更
+  pub applied: bool,
+  pub state: SyncState,
+  // 与上次同步的版本不同
+  pub changed: bool,
+  pub kept_local_keys: Vec<String>,
+  pub removed_keys: Vec<String>,
+  pub warnings: Vec<String>,
+}
+
+fn state_path(app_handle: &tauri::AppHandle) -> Option<std::path::PathBuf> {
+  app_handle.path().app_config_dir().ok().map(|p| p.join("registry-sync.json"))
+}
+
+fn read_state(app_handle: &tauri::AppHandle) -> Option<SyncState> {
+  let content = std::fs::read_to_string(state_path(app_handle)?).ok()?;
+  serde_json::from_str(&content).ok()
+}
+
+fn write_state(app_handle: &tauri::AppHandle, state: &SyncState) -> Result<(), String> {
+  let path = state_path(app_handle).ok_or_else(|| "config dir not available".to_string())?;
+  if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+  let content = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+  std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+// 内容哈希（FNV-1a），跨版本稳定，用于无版本信息的来源
+fn content_hash(content: &str) -> String {
+  let hash = content.bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+  format!("{:016x}", hash)
+}
+
+fn source_file(dir: &Path, source: &RegistrySource) -> std::path::PathBuf {
+  dir.join(source.file.as_deref().map(|f| f.trim()).filter(|f| !f.is_empty()).unwrap_or("components.json"))
+}
+
+fn git(repo: &str, args: &[&str], timeout: Option<Duration>) -> Result<String, String> {
+  let out = run_with_timeout(Command::new("git").arg("-C").arg(repo).args(args), timeout, &|_, _| {}).map_err(|e| format!("执行 git 失败：{}", e))?;
+  if !out.status.success() {
+    return Err(format!("git {} 失败：{}", args.join(" "), String::from_utf8_lossy(&out.stderr).trim()));
+  }
+  Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+// 获取来源内容与版本
+fn fetch(settings: &Settings, source: &RegistrySource, warnings: &mut Vec<String>) -> Result<(String, String), String> {
+  let location = source.location.trim();
+  if location.is_empty() { return Err("未配置注册表来源地址".into()); }
+  let policy = &settings.steps.view;
+  let timeout = if policy.timeout_secs > 0 { Some(Duration::from_secs(policy.timeout_secs)) } else { None };
+  match source.kind {
+    RegistrySourceKind::Path => {
+      let p = Path::new(location);
+      let file = if p.is_dir() { source_file(p, source) } else { p.to_path_buf() };
+      let content = std::fs::read_to_string(&file).map_err(|e| format!("读取 {} 失败：{}", file.display(), e))?;
+      let revision = content_hash(&content);
+      Ok((content, revision))
+    }
+    RegistrySourceKind::Git => {
+      // 拉取失败（如离线）时使用当前检出的内容
+      if let Err(e) = git(location, &["pull", "--ff-only"], timeout) { warnings.push(format!("{}，使用本地已有版本", e)); }
+      let revision = git(location, &["rev-parse", "HEAD"], timeout)?;
+      let file = source_file(Path::new(location), source);
+      let content = std::fs::read_to_string(&file).map_err(|e| format!("读取 {} 失败：{}", file.display(), e))?;
+      Ok((content, revision))
+    }
+    RegistrySourceKind::Url => {
+      let resp = npm::agent(&settings.network, location, timeout)?
+        .get(location)
+        .call()
+        .map_err(|e| format!("下载注册表失败（{}）：{}", location, e))?;
+      let etag = resp.header("ETag").map(|s| s.trim_matches('"').to_string());
+      let content = resp.into_string().map_err(|e| format!("读取响应失败：{}", e))?;
+      let revision = etag.unwrap_or_else(|| content_hash(&content));
+      Ok((content, revision))
+    }
+  }
+}
+
+// 合并：来源条目覆盖本地同 key 的非个人条目，来源中没有的非个人条目删除，个人条目（local）全部保留
+fn apply(local: &mut ComponentRegistry, incoming: ComponentRegistry, report: &mut SyncReport) {
+  let counts = &mut report.state.counts;
+  local.components.retain(|c| {
+    let keep = c.local || incoming.get(&c.key).is_some();
+    if !keep { report.removed_keys.push(c.key.clone()); }
+    keep
+  });
+  counts.removed = report.removed_keys.len();
+  for mut item in incoming.components {
+    item.local = false;
+    match local.components.iter().position(|c| c.key == item.key) {
+      None => { local.components.push(item); counts.added += 1; }
+      Some(pos) if local.components[pos].local => { report.kept_local_keys.push(item.key); counts.kept_local += 1; }
+      Some(pos) if local.components[pos] == item => counts.unchanged += 1,
+      Some(pos) => { local.components[pos] = item; counts.updated += 1; }
+    }
+  }
+}
+
+// 首次同步前的条目（不在内置默认注册表中的）都视为个人条目，避免被来源覆盖或删除
+fn mark_personal(registry: &mut ComponentRegistry) {
+  let builtin = components::default_registry();
+  for c in registry.components.iter_mut() {
+    if builtin.get(&c.key).is_none() { c.local = true; }
+  }
+}
+
+fn sync(app_handle: &tauri::AppHandle, confirm: bool) -> Result<SyncReport, String> {
+  let settings = config::load_settings(app_handle);
+  let source = settings.registry_source.clone().ok_or_else(|| "未配置共享注册表来源".to_string())?;
+  let mut warnings: Vec<String> = Vec::new();
+  let (content, revision) = fetch(&settings, &source, &mut warnings)?;
+
+  // 来源须自身通过校验
+  let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| format!("来源注册表格式错误：{}", e))?;
+  let (mut incoming, _) = ComponentRegistry::from_value(value)?;
+  incoming.normalize();
+  let errors = incoming.validate();
+  if !errors.is_empty() { return Err(format!("来源注册表校验失败：\n{}", components::format_errors(&errors))); }
+
+  let previous = read_state(app_handle);
+  let mut report = SyncReport {
+    applied: false,
+    changed: previous.as_ref().is_none_or(|p| p.revision != revision),
+    state: SyncState { source, synced_at: job::now_millis(), revision, counts: SyncCounts::default() },
+    kept_local_keys: Vec::new(),
+    removed_keys: Vec::new(),
+    warnings,
+  };
+  let mut registry = components::read(app_handle)?;
+  if previous.is_none() { mark_personal(&mut registry); }
+  apply(&mut registry, incoming, &mut report);
+  if !confirm { return Ok(report); }
+  components::save(app_handle, registry)?;
+  write_state(app_handle, &report.state)?;
+  report.applied = true;
+  Ok(report)
+}
+
+// 按设置中的来源同步组件注册表，并记录同步时间与来源版本；
+// confirm 不为 true 时只返回预览（将删除、保留与更新的条目），不写入任何文件
+#[tauri::command]
+pub async fn sync_component_registry(app_handle: tauri::AppHandle, confirm: Option<bool>) -> Result<SyncReport, String> {
+  let confirm = confirm.unwrap_or(false);
+  async_runtime::spawn_blocking(move || sync(&app_handle, confirm))
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// 最近一次同步的记录；从未同步时为 None
+#[tauri::command]
+pub fn read_registry_sync_state(app_handle: tauri::AppHandle) -> Result<Option<SyncState>, String> {
+  Ok(read_state(&app_handle))
+}
@@ -0,0 +1,180 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::ipc::Channel;
+use tauri::Emitter;
+
+use super::job;
+use super::jobs;
+use super::logs::{LogLine, LogWriter, Stream};
+
+// 事件名常量
+pub const EVT_LOG: &str = "project:create_log";
+pub const EVT_DONE: &str = "project:create_done";
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+  Pending,
+  Running,
+  Done,
+  Failed,
+  // 续跑时已在上次完成的步骤
+  Skipped,
+}
+
+// 单个步骤的结构化进度，index 从 0 开始；elapsed_ms 在步骤结束时给出
+#[derive(Clone, Debug, Serialize)]
+pub struct StepProgress {
+  pub id: String,
+  pub title: String,
+  pub index: usize,
+  pub total: usize,
+  pub status: StepStatus,
+  pub started_at: Option<u64>,
+  pub elapsed_ms: Option<u64>,
+  pub error: Option<String>,
+}
+
+// 经 IPC 通道发送给发起方的任务消息，仅送达该任务的订阅者
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "kebab-case")]
+pub enum CreateEvent {
+  Log { seq: usize, step: Option<String>, stream: Stream, line: String },
+  StepStarted(StepProgress),
+  StepFinished(StepProgress),
+  // 全部步骤的快照，percent 按已完成（含跳过）的步骤数计算
+  Progress { completed: usize, total: usize, percent: u8, steps: Vec<StepProgress> },
+  Done { success: bool, error: Option<String> },
+}
+
+pub fn progress_event(steps: &[StepProgress]) -> CreateEvent {
+  let total = steps.len();
+  let completed = steps.iter().filter(|s| matches!(s.status, StepStatus::Done | StepStatus::Skipped)).count();
+  let percent = (completed * 100).checked_div(total).unwrap_or(0) as u8;
+  CreateEvent::Progress { completed, total, percent, steps: steps.to_vec() }
+}
+
+// 创建命令的通道参数：提供 on_event 时默认不再广播全局事件，可用 broadcast 显式开启
+pub fn broadcast_default(on_event: &Option<Channel<CreateEvent>>, broadcast: Option<bool>) -> bool {
+  broadcast.unwrap_or(on_event.is_none())
+}
+
+// 任务输出的汇集点：每行写入磁盘日志（带时间、步骤与输出流标记），发给任务的通道订阅者，并按需广播全局事件
+pub struct Reporter {
+  app_handle: tauri::AppHandle,
+  job_id: String,
+  broadcast: bool,
+  step: Mutex<Option<String>>,
+  steps: Mutex<Vec<StepProgress>>,
+  log: Mutex<Option<LogWriter>>,
+}
+
+impl Reporter {
+  // broadcast 为 false 时（同步创建）不发事件，改写入应用日志
+  pub fn new(app_handle: &tauri::AppHandle, job_id: &str, broadcast: bool) -> Self {
+    let log = match LogWriter::create(app_handle, job_id) {
+      Ok(w) => Some(w),
+      Err(e) => { log::warn!("创建任务日志失败：{}", e); None }
+    };
+    Reporter {
+      app_handle: app_handle.clone(),
+      job_id: job_id.to_string(),
+      broadcast,
+      step: Mutex::new(None),
+      steps: Mutex::new(Vec::new()),
+      log: Mutex::new(log),
+    }
+  }
+
+  pub fn log_path(&self) -> Option<String> {
+    self.log.lock().ok()?.as_ref().map(|w| w.path().to_string_lossy().to_string())
+  }
+
+  fn set_step(&self, step: Option<&str>) {
+    if let Ok(mut s) = self.step.lock() { *s = step.map(|s| s.to_string()); }
+    jobs::set_step(&self.job_id, step);
+  }
+
+  // 登记本次执行的全部步骤（id, 标题），初始均为 pending
+  pub fn plan(&self, steps: &[(String, String)]) {
+    let total = steps.len();
+    let list: Vec<StepProgress> = steps
+      .iter()
+      .enumerate()
+      .map(|(index, (id, title))| StepProgress {
+        id: id.clone(), title: title.clone(), index, total,
+        status: StepStatus::Pending, started_at: None, elapsed_ms: None, error: None,
+      })
+      .collect();
+    if let Ok(mut s) = self.steps.lock() { *s = list; }
+    self.publish_progress();
+  }
+
+  // 修改某个步骤的状态，返回修改后的快照
+  fn update_step(&self, id: &str, f: impl FnOnce(&mut StepProgress)) -> Option<StepProgress> {
+    let mut steps = self.steps.lock().ok()?;
+    let step = steps.iter_mut().find(|s| s.id == id)?;
+    f(step);
+    Some(step.clone())
+  }
+
+  fn publish_progress(&self) {
+    let steps = match self.steps.lock() { Ok(s) => s.clone(), Err(_) => return };
+    jobs::set_steps(&self.job_id, &steps);
+    jobs::publish(&self.job_id, progress_event(&steps));
+  }
+
+  pub fn step_skipped(&self, id: &str) {
+    self.update_step(id, |s| s.status = StepStatus::Skipped);
+    self.publish_progress();
+  }
+
+  pub fn step_started(&self, id: &str) {
+    self.set_step(Some(id));
+    let snapshot = self.update_step(id, |s| { s.status = StepStatus::Running; s.started_at = Some(job::now_millis()); });
+    if let Some(p) = snapshot { jobs::publish(&self.job_id, CreateEvent::StepStarted(p)); }
+    self.publish_progress();
+  }
+
+  pub fn step_finished(&self, id: &str, result: &Result<(), String>) {
+    self.set_step(None);
+    let snapshot = self.update_step(id, |s| {
+      s.status = if result.is_ok() { StepStatus::Done } else { StepStatus::Failed };
+      s.elapsed_ms = s.started_at.map(|t| job::now_millis().saturating_sub(t));
+      s.error = result.as_ref().err().cloned();
+    });
+    if let Some(p) = snapshot { jobs::publish(&self.job_id, CreateEvent::StepFinished(p)); }
+    self.publish_progress();
+  }
+
+  pub fn line(&self, stream: Stream, line: &str) {
+    let step = self.step.lock().ok().and_then(|s| s.clone());
+    let entry = LogLine { ts: job::now_millis(), step, stream, line: line.to_string() };
+    if let Ok(mut w) = self.log.lock() {
+      if let Some(w) = w.as_mut() { w.write(&entry); }
+    }
+    let seq = jobs::push_line(&self.job_id, &entry);
+    if self.broadcast {
+      let _ = self.app_handle.emit(EVT_LOG, serde_json::json!({
+        "line": entry.line, "job_id": self.job_id, "step": entry.step, "stream": entry.stream, "seq": seq
+      }));
+    } else {
+      log::info!("{}", line);
+    }
+  }
+
+  pub fn info(&self, line: &str) {
+    self.line(Stream::Info, line);
+  }
+
+  // 通知订阅者任务结束（随后释放通道），并按需广播完成事件
+  pub fn done(&self, result: &Result<(), String>) {
+    jobs::finish(&self.job_id, result);
+    if !self.broadcast { return; }
+    let payload = match result {
+      Ok(()) => serde_json::json!({"success": true, "job_id": self.job_id}),
+      Err(e) => serde_json::json!({"success": false, "error": e, "job_id": self.job_id}),
+    };
+    let _ = self.app_handle.emit(EVT_DONE, payload);
+  }
+}
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::Manager;
+
+use super::config::Settings;
+use super::job::{self, JOB_FILE};
+use super::process::{pnpm_command, run_with_timeout};
+use super::project::CreateOptions;
+
+// 脚手架输出缓存：按 (脚手架, 版本, 模板变体) 缓存未经修改的脚手架产物，后续创建直接复制
+const META_FILE: &str = "meta.json";
+const FILES_DIR: &str = "files";
+
+// 不进入缓存的路径：依赖目录与版本库由后续步骤重新生成
+const SKIP: [&str; 3] = ["node_modules", ".git", JOB_FILE];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheEntry {
+  pub id: String,
+  pub scaffolder: String,
+  pub version: String,
+  pub variant: String,
+  // 生成缓存时使用的项目名，复制时替换为新名称
+  pub original_name: String,
+  pub created_at: u64,
+  #[serde(default)]
+  pub size_bytes: u64,
+}
+
+// 脚手架包描述与模板变体；与 project.rs 中的脚手架命令保持一致。
+// create-vue 的变体由语言与 router / pinia 选项组成（如 "ts-router-pinia"）
+pub fn scaffolder_for(opts: &CreateOptions) -> Option<(&'static str, String)> {
+  match (opts.version.as_str(), opts.lang.as_str()) {
+    ("vue3", lang) if opts.create_vue => {
+      let mut variant = lang.to_string();
+      for f in ["router", "pinia"] {
+        if opts.features.iter().any(|x| x == f) { variant.push('-'); variant.push_str(f); }
+      }
+      Some(("create-vue@latest", variant))
+    }
+    ("vue3", "ts") => Some(("create-vite@latest", "vue-ts".into())),
+    ("vue3", _) => Some(("create-vite@latest", "vue".into())),
+    ("vue2", _) => Some(("@vue/cli@5", "vue2-babel".into())),
+    _ => None,
+  }
+}
+
+fn cache_root(app_handle: &tauri::AppHandle) -> Option<PathBuf> {
+  app_handle.path().app_data_dir().ok().map(|p| p.join("scaffold-cache"))
+}
+
+fn package_name(spec: &str) -> &str {
+  match spec.rfind('@') { Some(i) if i > 0 => &spec[..i], _ => spec }
+}
+
+fn entry_id(scaffolder: &str, version: &str, variant: &str) -> String {
+  format!("{}@{}__{}", package_name(scaffolder), version, variant).replace('/', "+")
+}
+
+// 解析脚手架实际版本（如 create-vite@latest -> 6.1.0）；查询失败返回 None
+pub fn resolve_version(pnpm_bin: &str, settings: &Settings, scaffolder: &str) -> Option<String> {
+  let timeout = if settings.steps.view.timeout_secs > 0 { Some(Duration::from_secs(settings.steps.view.timeout_secs)) } else { None };
+  let out = run_with_timeout(pnpm_command(pnpm_bin, &settings.network).args(["view", scaffolder, "version", "--json"]), timeout, &|_, _| {}).ok()?;
+  if !out.status.success() { return None; }
+  // 范围描述（如 @vue/cli@5）会返回版本数组，取最后一个（最高版本）
+  match serde_json::from_slice::<serde_json::Value>(&out.stdout).ok()? {
+    serde_json::Value::String(s) => Some(s),
+    serde_json::Value::Array(arr) => arr.last().and_then(|x| x.as_str()).map(|s| s.to_string()),
+    _ => None,
+  }
+}
+
+fn read_meta(dir: &Path) -> Option<CacheEntry> {
+  let content = std::fs::read_to_string(dir.join(META_FILE)).ok()?;
+  serde_json::from_str(&content).ok()
+}
+
+pub fn list(app_handle: &tauri::AppHandle) -> Vec<CacheEntry> {
+  let root = match cache_root(app_handle) { Some(r) => r, None => return Vec::new() };
+  let mut entries: Vec<CacheEntry> = std::fs::read_dir(&root)
+    .map(|it| it.flatten().filter_map(|e| read_meta(&e.path())).collect())
+    .unwrap_or_default();
+  entries.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+  entries
+}
+
+// 查找缓存：指定版本时精确匹配；版本未知（如离线无法查询）时取该脚手架与变体最新的缓存
+pub fn find(app_handle: &tauri::AppHandle, scaffolder: &str, version: Option<&str>, variant: &str) -> Option<CacheEntry> {
+  let name = package_name(scaffolder);
+  list(app_handle)
+    .into_iter()
+    .find(|e| e.scaffolder == name && e.variant == variant && version.is_none_or(|v| e.version == v))
+}
+
+fn copy_dir(src: &Path, dst: &Path, skip: &[&str]) -> Result<u64, String> {
+  std::fs::create_dir_all(dst).map_err(|e| e.to_string())?;
+  let mut size = 0;
+  for entry in std::fs::read_dir(src).map_err(|e| e.to_string())? {
+    let entry = entry.map_err(|e| e.to_string())?;
+    let name = entry.file_name();
+    if skip.iter().any(|s| name.to_str() == Some(s)) { continue; }
+    let from = entry.path();
+    let to = dst.join(&name);
+    let meta = from.symlink_metadata().map_err(|e| e.to_string())?;
+    if meta.is_dir() {
+      size += copy_dir(&from, &to, &[])?;
+    } else if meta.is_file() {
+      size += std::fs::copy(&from, &to).map_err(|e| e.to_string())?;
+    }
+  }
+  Ok(size)
+}
+
+// 将刚生成的脚手架产物写入缓存（先写临时目录再改名，避免半成品被读取）
+pub fn store(app_handle: &tauri::AppHandle, scaffolder: &str, version: &str, variant: &str, project_dir: &Path, name: &str) -> Result<(), String> {
+  let root = cache_root(app_handle).ok_or_else(|| "data dir not available".to_string())?;
+  let id = entry_id(scaffolder, version, variant);
+  let target = root.join(&id);
+  let staging = root.join(format!(".{}-{}", id, job::now_millis()));
+  let write_staging = || -> Result<(), String> {
+    let size = copy_dir(project_dir, &staging.join(FILES_DIR), &SKIP)?;
+    let meta = CacheEntry {
+      id: id.clone(),
+      scaffolder: package_name(scaffolder).to_string(),
+      version: version.to_string(),
+      variant: variant.to_string(),
+      original_name: name.to_string(),
+      created_at: job::now_millis(),
+      size_bytes: size,
+    };
+    let content = serde_json::to_string_pretty(&meta).map_err(|e| e.to_string())?;
+    std::fs::write(staging.join(META_FILE), content).map_err(|e| e.to_string())
+  };
+  // 写入失败时删除未完成的临时目录
+  if let Err(e) = write_staging() {
+    let _ = std::fs::remove_dir_all(&staging);
+    return Err(e);
+  }
+  if target.exists() { let _ = std::fs::remove_dir_all(&target); }
+  std::fs::rename(&staging, &target).map_err(|e| { let _ = std::fs::remove_dir_all(&staging); e.to_string() })
+}
+
+// 替换 <title>原名</title> 或 "# 原名" 标题行；无匹配时返回 None
+fn rename_tokens(content: &str, from: &str, to: &str, markdown: bool) -> Option<String> {
+  if markdown {
+    let heading = format!("# {}", from);
+    if !content.lines().any(|l| l.trim_end() == heading) { return None; }
+    // 保留原有换行符
+    let out: String = content
+      .split_inclusive('\n')
+      .map(|l| {
+        let body = l.trim_end_matches(['\r', '\n']);
+        if body.trim_end() == heading { format!("# {}{}", to, &l[body.len()..]) } else { l.to_string() }
+      })
+      .collect();
+    return Some(out);
+  }
+  let title = format!("<title>{}</title>", from);
+  content.contains(&title).then(|| content.replace(&title, &format!("<title>{}</title>", to)))
+}
+
+// 从缓存复制到项目目录，并将项目名从缓存时的名称改为新名称
+pub fn restore(app_handle: &tauri::AppHandle, entry: &CacheEntry, project_dir: &Path, name: &str) -> Result<(), String> {
+  let root = cache_root(app_handle).ok_or_else(|| "data dir not available".to_string())?;
+  job::clear_except_state(project_dir)?;
+  copy_dir(&root.join(&entry.id).join(FILES_DIR), project_dir, &[])?;
+
+  let pkg_path = project_dir.join("package.json");
+  if let Ok(content) = std::fs::read_to_string(&pkg_path) {
+    if let Ok(mut v) = serde_json::from_str::<serde_json::Value>(&content) {
+      if let Some(obj) = v.as_object_mut() { obj.insert("name".into(), serde_json::Value::String(name.to_string())); }
+      let out = serde_json::to_string_pretty(&v).map_err(|e| e.to_string())?;
+      std::fs::write(&pkg_path, out + "\n").map_err(|e| e.to_string())?;
+    }
+  }
+  // 只替换展示项目名的完整位置（HTML 的 <title>、README 的一级标题），避免误改包含该名称的其他文字
+  if entry.original_name != name {
+    for rel in ["README.md", "index.html", "public/index.html"] {
+      let path = project_dir.join(rel);
+      let Ok(content) = std::fs::read_to_string(&path) else { continue };
+      if let Some(out) = rename_tokens(&content, &entry.original_name, name, rel.ends_with(".md")) {
+        std::fs::write(&path, out).map_err(|e| e.to_string())?;
+      }
+    }
+  }
+  Ok(())
+}
+
+#[tauri::command]
+pub fn list_scaffold_cache(app_handle: tauri::AppHandle) -> Result<Vec<CacheEntry>, String> {
+  Ok(list(&app_handle))
+}
+
+// 清理脚手架缓存：ids 缺省或为空列表时清理全部，返回清理的条目数
+#[tauri::command]
+pub fn purge_scaffold_cache(app_handle: tauri::AppHandle, ids: Option<Vec<String>>) -> Result<usize, String> {
+  let root = cache_root(&app_handle).ok_or_else(|| "data dir not available".to_string())?;
+  let mut removed = 0;
+  for entry in list(&app_handle) {
+    if ids.as_ref().is_some_and(|ids| !ids.is_empty() && !ids.contains(&entry.id)) { continue; }
+    std::fs::remove_dir_all(root.join(&entry.id)).map_err(|e| e.to_string())?;
+    removed += 1;
+  }
+  Ok(removed)
+}
@@ -0,0 +1,131 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use tauri::Emitter;
+use tauri::async_runtime;
+
+use super::config;
+use super::job;
+use super::process::{pnpm_command, resolve_pnpm, run_with_policy, run_with_timeout};
+use super::components::{self, ComponentRegistry};
+use super::project::split_spec;
+use super::templates;
+
+const EVT_PREWARM: &str = "store:prewarm_progress";
+
+// 预热组合：框架版本 × 语言，与内置模板一一对应
+const TARGETS: [(&str, &str); 3] = [("vue3", "ts"), ("vue3", "js"), ("vue2", "js")];
+
+// 统计目录占用（不跟随符号链接）
+fn dir_size(path: &Path) -> u64 {
+  let mut total = 0;
+  if let Ok(entries) = std::fs::read_dir(path) {
+    for entry in entries.flatten() {
+      let meta = match entry.path().symlink_metadata() { Ok(m) => m, Err(_) => continue };
+      if meta.is_dir() { total += dir_size(&entry.path()); } else if meta.is_file() { total += meta.len(); }
+    }
+  }
+  total
+}
+
+// 汇总某个组合需要的全部依赖（name, range）：模板 package.json + 注册表中支持该组合的所有组件；
+// 按 name@range 去重，同一个包的不同范围都会预热
+fn collect_dependencies(app_handle: &tauri::AppHandle, registry: &ComponentRegistry, version: &str, lang: &str) -> Result<BTreeSet<(String, String)>, String> {
+  let variant = templates::variant(version, lang).ok_or_else(|| "unknown version".to_string())?;
+  let mut deps: BTreeSet<(String, String)> = templates::dependencies(app_handle, variant)?.into_iter().collect();
+  for item in registry.components.iter() {
+    if let Some((_, packages)) = item.packages_for(version, lang) {
+      for spec in packages {
+        let (name, range) = split_spec(&spec);
+        deps.insert((name.to_string(), range.unwrap_or("latest").to_string()));
+      }
+    }
+  }
+  Ok(deps)
+}
+
+// package.json 的 dependencies：同名包的其余范围以别名（npm:name@range）安装
+fn manifest_dependencies(deps: &BTreeSet<(String, String)>) -> serde_json::Map<String, serde_json::Value> {
+  let mut out = serde_json::Map::new();
+  for (name, range) in deps {
+    if !out.contains_key(name) { out.insert(name.clone(), range.clone().into()); continue; }
+    let alias = (2..).map(|n| format!("{}-prewarm-{}", name, n)).find(|a| !out.contains_key(a)).unwrap_or_default();
+    out.insert(alias, format!("npm:{}@{}", name, range).into());
+  }
+  out
+}
+
+fn store_path(pnpm_bin: &str, settings: &config::Settings) -> Option<String> {
+  // store path 为本地查询，给固定超时即可
+  let timeout = Some(std::time::Duration::from_secs(30));
+  run_with_timeout(pnpm_command(pnpm_bin, &settings.network).args(["store", "path"]), timeout, &|_, _| {})
+    .ok()
+    .filter(|o| o.status.success())
+    .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    .filter(|s| !s.is_empty())
+}
+
+fn prewarm(app_handle: &tauri::AppHandle) -> Result<serde_json::Value, String> {
+  let pnpm_bin = resolve_pnpm().ok_or_else(|| "pnpm not found".to_string())?;
+  let settings = config::load_settings(app_handle);
+  let registry = components::load(app_handle);
+  let total = TARGETS.len();
+  let progress = |index: usize, target: &str, status: &str, message: &str| {
+    let _ = app_handle.emit(EVT_PREWARM, serde_json::json!({
+      "index": index, "total": total, "target": target, "status": status, "message": message
+    }));
+  };
+
+  let store_path = store_path(&pnpm_bin, &settings);
+  let size_before = store_path.as_deref().map(|p| dir_size(Path::new(p))).unwrap_or(0);
+  let mut results = Vec::new();
+  for (i, (version, lang)) in TARGETS.iter().enumerate() {
+    let target = format!("{}-{}", version, lang);
+    let deps = collect_dependencies(app_handle, &registry, version, lang)?;
+    progress(i + 1, &target, "running", &format!("正在拉取 {} 个依赖...", deps.len()));
+
+    // 在临时目录中联网安装一次，使包与元数据进入 pnpm store / 缓存，供离线安装使用
+    let dir = std::env::temp_dir().join(format!("acps-store-prewarm-{}-{}", target, job::now_millis()));
+    let manifest = serde_json::json!({ "name": "store-prewarm", "private": true, "dependencies": manifest_dependencies(&deps) });
+    let emit = |line: &str| progress(i + 1, &target, "running", line);
+    let res = std::fs::create_dir_all(&dir)
+      .and_then(|_| std::fs::write(dir.join("package.json"), manifest.to_string()))
+      .map_err(|e| e.to_string())
+      .and_then(|_| {
+        let make = || {
+          let mut c = pnpm_command(&pnpm_bin, &settings.network);
+          c.current_dir(&dir).args(["install", "--ignore-scripts", "--no-lockfile", "--prefer-offline"]);
+          c
+        };
+        run_with_policy(&format!("prewarm:{}", target), &settings.steps.install, &|_, l| emit(l), &make).map(|_| ())
+      });
+    let _ = std::fs::remove_dir_all(&dir);
+    match &res {
+      Ok(()) => progress(i + 1, &target, "done", "完成"),
+      Err(e) => progress(i + 1, &target, "failed", e),
+    }
+    results.push(serde_json::json!({
+      "target": target,
+      "packages": deps.len(),
+      "success": res.is_ok(),
+      "error": res.err(),
+    }));
+  }
+
+  // store 总大小，以及本次预热新增的大小
+  let store_bytes = store_path.as_deref().map(|p| dir_size(Path::new(p))).unwrap_or(0);
+
+  Ok(serde_json::json!({
+    "targets": results,
+    "store_path": store_path,
+    "store_bytes": store_bytes,
+    "added_bytes": store_bytes.saturating_sub(size_before),
+  }))
+}
+
+// 预热 pnpm store：按注册表与内置模板解析每个组合所需的依赖并提前拉取，过程通过事件推送进度
+#[tauri::command]
+pub async fn prewarm_store(app_handle: tauri::AppHandle) -> Result<serde_json::Value, String> {
+  async_runtime::spawn_blocking(move || prewarm(&app_handle))
+    .await
+    .map_err(|e| format!("预热 store 失败: {e}"))?
+}
@@ -0,0 +1,94 @@
+use include_dir::{include_dir, Dir};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use super::job;
+
+// 内置脚手架模板（src-tauri/templates），离线创建时替代 pnpm dlx 脚手架
+static TEMPLATES: Dir<'static> = include_dir!("$CARGO_MANIFEST_DIR/templates");
+
+// 模板中的 dotfile 以下划线前缀存放，生成时还原
+const RENAMES: [(&str, &str); 1] = [("_gitignore", ".gitignore")];
+
+// 根据 Vue 版本与语言选择模板；Vue 2 仅提供 babel 版本，与 Vue CLI 预设一致
+pub fn variant(version: &str, lang: &str) -> Option<&'static str> {
+  match (version, lang) {
+    ("vue3", "ts") => Some("vue3-ts"),
+    ("vue3", _) => Some("vue3-js"),
+    ("vue2", _) => Some("vue2-js"),
+    _ => None,
+  }
+}
+
+// 应用数据目录下的同名模板优先，便于不重新打包即可更新模板
+fn override_dir(app_handle: &tauri::AppHandle, variant: &str) -> Option<PathBuf> {
+  let dir = app_handle.path().app_data_dir().ok()?.join("templates").join(variant);
+  if dir.is_dir() { Some(dir) } else { None }
+}
+
+fn collect_embedded(dir: &Dir<'_>, root: &Path, out: &mut Vec<(PathBuf, Vec<u8>)>) {
+  for f in dir.files() {
+    if let Ok(rel) = f.path().strip_prefix(root) { out.push((rel.to_path_buf(), f.contents().to_vec())); }
+  }
+  for d in dir.dirs() { collect_embedded(d, root, out); }
+}
+
+fn collect_disk(dir: &Path, root: &Path, out: &mut Vec<(PathBuf, Vec<u8>)>) -> Result<(), String> {
+  for entry in std::fs::read_dir(dir).map_err(|e| e.to_string())? {
+    let path = entry.map_err(|e| e.to_string())?.path();
+    if path.is_dir() {
+      collect_disk(&path, root, out)?;
+    } else if let Ok(rel) = path.strip_prefix(root) {
+      out.push((rel.to_path_buf(), std::fs::read(&path).map_err(|e| e.to_string())?));
+    }
+  }
+  Ok(())
+}
+
+// 模板文件列表：(相对路径, 内容)
+fn files(app_handle: &tauri::AppHandle, variant: &str) -> Result<Vec<(PathBuf, Vec<u8>)>, String> {
+  let mut out = Vec::new();
+  if let Some(dir) = override_dir(app_handle, variant) {
+    collect_disk(&dir, &dir, &mut out)?;
+  } else {
+    let dir = TEMPLATES.get_dir(variant).ok_or_else(|| format!("未找到内置模板：{}", variant))?;
+    collect_embedded(dir, Path::new(variant), &mut out);
+  }
+  Ok(out)
+}
+
+// 模板 package.json 中声明的依赖（dependencies 与 devDependencies），供离线预检使用
+pub fn dependencies(app_handle: &tauri::AppHandle, variant: &str) -> Result<BTreeMap<String, String>, String> {
+  let (_, content) = files(app_handle, variant)?
+    .into_iter()
+    .find(|(rel, _)| rel == Path::new("package.json"))
+    .ok_or_else(|| format!("模板 {} 缺少 package.json", variant))?;
+  let v: serde_json::Value = serde_json::from_slice(&content).map_err(|e| e.to_string())?;
+  let mut deps = BTreeMap::new();
+  for field in ["dependencies", "devDependencies"] {
+    if let Some(map) = v.get(field).and_then(|x| x.as_object()) {
+      for (k, ver) in map { deps.insert(k.clone(), ver.as_str().unwrap_or("latest").to_string()); }
+    }
+  }
+  Ok(deps)
+}
+
+// 以模板生成项目：清空目标目录（保留任务状态文件）后写入模板文件，并替换 {{name}} 占位符
+pub fn render(app_handle: &tauri::AppHandle, variant: &str, project_dir: &Path, name: &str) -> Result<(), String> {
+  let files = files(app_handle, variant)?;
+  job::clear_except_state(project_dir)?;
+  for (rel, content) in files {
+    let mut target = project_dir.join(&rel);
+    if let Some(file_name) = rel.file_name().and_then(|n| n.to_str()) {
+      if let Some((_, to)) = RENAMES.iter().find(|(from, _)| *from == file_name) { target.set_file_name(to); }
+    }
+    if let Some(dir) = target.parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+    match String::from_utf8(content) {
+      Ok(text) => std::fs::write(&target, text.replace("{{name}}", name)),
+      Err(bin) => std::fs::write(&target, bin.into_bytes()),
+    }
+    .map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
@@ -14,6 +14,9 @@ pub fn run() {
             .build(),
         )?;
       }
+      // 启动时按保留天数清理过期的任务日志
+      let retention = commands::config::load_settings(app.handle()).log_retention_days;
+      if let Err(e) = commands::logs::prune(app.handle(), retention) { log::warn!("清理任务日志失败：{}", e); }
       Ok(())
     })
     .invoke_handler(tauri::generate_handler![
@@ -22,10 +25,47 @@ pub fn run() {
       commands::project::check_target_dir,
       commands::project::create_project,
       commands::project::create_project_async,
+      commands::project::resume_project_job,
+      commands::batch::create_batch,
+      commands::presets::list_presets,
+      commands::presets::save_preset,
+      commands::presets::delete_preset,
+      commands::presets::rename_preset,
+      commands::presets::create_from_preset,
+      commands::presets::export_presets,
+      commands::presets::import_presets,
+      commands::jobs::list_jobs,
+      commands::jobs::subscribe_job,
+      commands::history::list_history,
+      commands::history::get_history_record,
+      commands::history::delete_history_records,
+      commands::history::rerun_history,
+      commands::logs::list_logs,
+      commands::logs::read_log,
+      commands::logs::export_log,
+      commands::logs::prune_logs,
       commands::config::read_default_directory,
+      commands::config::read_settings,
+      commands::config::save_settings,
       commands::registry::read_component_registry,
       commands::registry::save_component_registry,
+      commands::registry::validate_component_registry,
+      commands::registry::read_component_groups,
+      commands::registry::check_features,
+      commands::registry_share::export_component_registry,
+      commands::registry_share::import_component_registry,
+      commands::registry_sync::sync_component_registry,
+      commands::registry_sync::read_registry_sync_state,
+      commands::registry::fetch_package_meta,
       commands::registry::fetch_npm_versions,
+      commands::registry::search_npm_packages,
+      commands::registry::draft_component,
+      commands::npm_cache::clear_npm_cache,
+      commands::compat::check_component_compat,
+      commands::registry::check_registry_settings,
+      commands::store::prewarm_store,
+      commands::scaffold_cache::list_scaffold_cache,
+      commands::scaffold_cache::purge_scaffold_cache,
       commands::menu::read_tool_menu,
       commands::menu::save_tool_menu,
     ])